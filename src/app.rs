@@ -0,0 +1,1757 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwap;
+use atomic::Atomic;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use nohash_hasher::IntSet;
+use parking_lot::Mutex;
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
+use tui::{layout::Position, text::Line};
+
+use crate::{
+    backend::{Backend, Operation},
+    catalog::{Catalog, Installs},
+    column::Column,
+    config::Config,
+    editor::LineEditor,
+    error::Error,
+    history::History,
+    installed::{Installed, Leaves, Sizes},
+    interface::{get_info, search, sort_shown},
+    keymap::{Action, Key, Keymap},
+    log,
+    message::Message,
+    mode::Mode,
+    open, sets,
+    shown::Shown,
+    sort::{Context, Sort, SortKey},
+    theme::Theme,
+    ui::{plural, Areas, InfoPosition, Panes},
+    view::View,
+};
+
+/// How soon a second click on the same row has to follow the first to count as a double-click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// How long each frame of the spinner stays up, and so how often to redraw while it turns.
+pub const SPINNER_FRAME: Duration = Duration::from_millis(80);
+
+/// Where the Ruby file of a package is, and the action it was looked up for.
+type FoundSource = (Action, Result<PathBuf, Error>);
+
+/// Why the event loop should stop.
+pub enum Exit {
+    Quit,
+    /// Restore the terminal and hand it over to `brew`.
+    Run(Operation, Vec<String>),
+}
+
+/// The scroll position of the keybind overlay, and the mode whose keybinds it lists.
+pub struct Help {
+    pub mode: Mode,
+    pub scroll: u16,
+}
+
+/// The cursor in the basket panel, which lists every selected package.
+pub struct Basket {
+    pub current: usize,
+}
+
+/// Which pane keys go to in select mode.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Focus {
+    #[default]
+    Results,
+    Info,
+}
+
+/// Where each line of info starts once wrapped to the pane, as last drawn.
+#[derive(Default)]
+pub struct InfoLayout {
+    pub starts: Vec<u16>,
+    /// How many lines the info takes up once wrapped.
+    pub lines: u16,
+    /// How many of them fit in the pane.
+    pub height: u16,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    SaveSet,
+    LoadSet,
+    InfoSearch,
+}
+
+/// A question asked in place of the search box, such as the name of a set to save.
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: LineEditor,
+    /// Why the last answer was rejected, shown until the next one.
+    pub error: Option<String>,
+}
+
+impl Prompt {
+    fn new(kind: PromptKind) -> Self {
+        Self {
+            kind,
+            input: LineEditor::default(),
+            error: None,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let question = match self.kind {
+            PromptKind::SaveSet => "Save set as:",
+            PromptKind::LoadSet => "Load set:",
+            PromptKind::InfoSearch => "Find in info:",
+        };
+
+        match &self.error {
+            Some(error) => format!(" {error}. {question} "),
+            None => format!(" {question} "),
+        }
+    }
+}
+
+/// An in-progress search through past queries, started with C-r.
+pub struct ReverseSearch {
+    pub pattern: String,
+    /// The history entry currently shown, `None` if nothing matches the pattern.
+    pub found: Option<usize>,
+    /// The query to restore if the search is cancelled.
+    original: String,
+}
+
+pub struct App {
+    pub backend: Arc<dyn Backend>,
+    pub keymap: Keymap,
+    pub query: LineEditor,
+    pub history: History,
+    pub reverse_search: Option<ReverseSearch>,
+    pub shown: Arc<ArcSwap<Shown>>,
+    pub mode: Arc<Atomic<Mode>>,
+    pub current: usize,
+    pub selected: IntSet<usize>,
+    /// The row visual mode was started on, the range runs from here to `current`.
+    pub visual_anchor: usize,
+    pub info_scroll: u16,
+    pub info: Arc<Mutex<Vec<Line<'static>>>>,
+    /// What was last searched for in the info pane, highlighted until cleared.
+    pub info_search: Option<String>,
+    pub info_layout: RefCell<InfoLayout>,
+    pub focus: Focus,
+    pub panes: Panes,
+    pub theme: &'static Theme,
+    /// Whether anything on screen is out of date.
+    pub dirty: bool,
+    /// Tells the event loop that a background task changed something.
+    changes: UnboundedSender<()>,
+    pub all_packages: Arc<OnceLock<&'static Catalog>>,
+    pub installed: Arc<OnceLock<Installed>>,
+    /// Only fetched once something is sorted by installs or shows them, as it means several
+    /// downloads.
+    pub installs: Arc<OnceLock<Installs>>,
+    /// Only measured once something is sorted by size, as it means walking every keg.
+    pub sizes: Arc<OnceLock<Sizes>>,
+    /// Only listed once the leaves view is opened, as it means asking `brew`.
+    pub leaves: Arc<OnceLock<Leaves>>,
+    /// Shown in the status bar while there are no results.
+    pub error_msg: Arc<Mutex<Message>>,
+    /// What the last action did, shown in the status bar until the next search.
+    pub outcome: Option<String>,
+    /// Set when asked to suspend, for the event loop to hand the terminal back to the shell.
+    pub suspend: bool,
+    /// A program such as an editor, for the event loop to hand the terminal over to.
+    pub launch: Option<Command>,
+    /// Set once `brew` has said where the Ruby file of a package is.
+    source: Arc<Mutex<Option<FoundSource>>>,
+    pub columns: Vec<Column>,
+    pub sort: Sort,
+    pub view: View,
+    pub help: Option<Help>,
+    pub basket: Option<Basket>,
+    pub prompt: Option<Prompt>,
+    /// How many rows of results fit on screen, updated whenever the terminal is measured.
+    pub per_page: usize,
+    /// The position in the results and height of each row of the table, as last drawn.
+    pub result_rows: RefCell<Vec<(usize, u16)>>,
+    /// The cursor each view was left at, so switching back returns to the same place.
+    view_cursors: [usize; View::ALL.len()],
+    /// The query the current results were found with, which may since have been edited.
+    searched: String,
+    /// When and where the list was last clicked, to detect double-clicks.
+    last_click: Option<(Instant, usize)>,
+    /// Whether the splitter between the panes is being dragged.
+    dragging: bool,
+    /// When breweri started, which the spinner is timed from.
+    started: Instant,
+    preselect: Vec<String>,
+    search_task: Option<JoinHandle<()>>,
+    /// Finding info about the package at this index.
+    info_task: Option<(usize, JoinHandle<()>)>,
+    installs_task: Option<JoinHandle<()>>,
+    sizes_task: Option<JoinHandle<()>>,
+    source_task: Option<JoinHandle<()>>,
+}
+
+impl App {
+    pub fn new(config: Config, backend: Arc<dyn Backend>, changes: UnboundedSender<()>) -> Self {
+        Self {
+            backend,
+            keymap: config.keymap,
+            query: LineEditor::new(config.query.unwrap_or_default()),
+            history: History::load(),
+            reverse_search: None,
+            shown: Arc::new(ArcSwap::new(Arc::new(Shown::Few(Vec::new())))),
+            mode: Arc::new(Atomic::new(Mode::Insert)),
+            current: 0,
+            selected: IntSet::default(),
+            visual_anchor: 0,
+            info_scroll: 0,
+            info_search: None,
+            info_layout: RefCell::new(InfoLayout::default()),
+            focus: Focus::default(),
+            panes: config.panes,
+            theme: config.theme,
+            info: Arc::new(Mutex::new(Vec::new())),
+            dirty: true,
+            changes,
+            all_packages: Arc::new(OnceLock::new()),
+            installed: Arc::new(OnceLock::new()),
+            installs: Arc::new(OnceLock::new()),
+            sizes: Arc::new(OnceLock::new()),
+            leaves: Arc::new(OnceLock::new()),
+            error_msg: Arc::new(Mutex::new(Message::TrySearch)),
+            outcome: None,
+            suspend: false,
+            launch: None,
+            source: Arc::new(Mutex::new(None)),
+            columns: config.columns,
+            sort: config.sort,
+            view: config.view,
+            help: None,
+            basket: None,
+            prompt: None,
+            per_page: 1,
+            result_rows: RefCell::new(Vec::new()),
+            view_cursors: [0; View::ALL.len()],
+            searched: String::new(),
+            last_click: None,
+            dragging: false,
+            started: Instant::now(),
+            preselect: config.select,
+            search_task: None,
+            info_task: None,
+            installs_task: None,
+            sizes_task: None,
+            source_task: None,
+        }
+    }
+
+    /// The mode keys are looked up in, which is basket mode while the basket is open and info mode
+    /// while the info pane has focus in select mode.
+    pub fn mode(&self) -> Mode {
+        match self.mode.load(Ordering::Relaxed) {
+            _ if self.basket.is_some() => Mode::Basket,
+            Mode::Select if self.focus == Focus::Info => Mode::Info,
+            mode => mode,
+        }
+    }
+
+    fn set_mode(&self, mode: Mode) {
+        self.mode.store(mode, Ordering::Relaxed);
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn shown_len(&self) -> usize {
+        (*self.shown)
+            .load()
+            .len()
+            .unwrap_or(self.all_packages.get().map(|p| p.len()).unwrap_or_default())
+    }
+
+    /// The query the current results were found with.
+    pub fn searched(&self) -> &str {
+        &self.searched
+    }
+
+    /// What is being done in the background, for the status bar.
+    pub fn activities(&self) -> Vec<&'static str> {
+        let running =
+            |task: &Option<JoinHandle<()>>| task.as_ref().is_some_and(|t| !t.is_finished());
+
+        let mut activities = Vec::new();
+        if running(&self.search_task) {
+            activities.push(if self.all_packages.get().is_none() {
+                "listing packages"
+            } else if self.installed.get().is_none() {
+                "checking installed packages"
+            } else {
+                "searching"
+            });
+        }
+        if running(&self.installs_task) {
+            activities.push("fetching install counts");
+        }
+        // Only cleared once the results are sorted by them
+        if self.sizes_task.is_some() {
+            activities.push("measuring sizes");
+        }
+        if self
+            .info_task
+            .as_ref()
+            .is_some_and(|(_, t)| !t.is_finished())
+        {
+            activities.push("finding info");
+        }
+        if self.source_task.as_ref().is_some_and(|t| !t.is_finished()) {
+            activities.push("finding source");
+        }
+        activities
+    }
+
+    pub fn spinner(&self) -> char {
+        let frame = self.started.elapsed().as_millis() / SPINNER_FRAME.as_millis();
+        SPINNER[frame as usize % SPINNER.len()]
+    }
+
+    pub fn real_idx(&self, idx: usize) -> usize {
+        (*self.shown).load().get(idx).unwrap_or(idx)
+    }
+
+    /// The packages in the visual range, if in visual mode.
+    pub fn visual_range(&self) -> Option<Vec<usize>> {
+        if self.mode() != Mode::Visual {
+            return None;
+        }
+
+        let (start, end) = if self.visual_anchor <= self.current {
+            (self.visual_anchor, self.current)
+        } else {
+            (self.current, self.visual_anchor)
+        };
+        Some((start..=end).map(|i| self.real_idx(i)).collect())
+    }
+
+    /// Applies the current sort to the results, keeping the cursor on the same package.
+    fn resort(&mut self) {
+        let (Some(all_packages), Some(installed)) = (self.all_packages.get(), self.installed.get())
+        else {
+            return;
+        };
+
+        if self.sort.key == SortKey::Size && self.sizes.get().is_none() {
+            // Sorted again by update once they have been measured
+            if self.sizes_task.is_none() {
+                let sizes = self.sizes.clone();
+                let installed = self.installed.clone();
+                let all_packages = *all_packages;
+                let backend = self.backend.clone();
+                let changes = self.changes.clone();
+                self.sizes_task = Some(tokio::spawn(async move {
+                    let installed = installed.get().unwrap();
+                    let result = backend.sizes(all_packages, installed).await;
+                    sizes.get_or_init(|| result);
+                    let _ = changes.send(());
+                }));
+            }
+            return;
+        }
+
+        let ctx = Context {
+            catalog: all_packages,
+            installed,
+            installs: self.installs.get(),
+            sizes: self.sizes.get(),
+            query: &self.searched,
+            leaves: self.leaves.get(),
+        };
+        let current = self.real_idx(self.current);
+        let shown = sort_shown(&self.shown.load(), &ctx, self.sort);
+        self.current = shown.get_vec().map_or(current, |v| {
+            v.iter().position(|&i| i == current).unwrap_or_default()
+        });
+        self.shown.store(shown.into());
+        self.mark_dirty();
+    }
+
+    /// Fetches the install counts in the background once the package list is loaded, if they are
+    /// sorted by or shown. They don't hold up the results, which are sorted again once they
+    /// arrive.
+    fn fetch_installs(&mut self) {
+        let needed = matches!(self.sort.key, SortKey::Installs(_))
+            || self.columns.contains(&Column::Installs);
+        let Some(&all_packages) = self.all_packages.get() else {
+            return;
+        };
+        if !needed || self.installs.get().is_some() || self.installs_task.is_some() {
+            return;
+        }
+
+        let installs = self.installs.clone();
+        let backend = self.backend.clone();
+        let changes = self.changes.clone();
+        self.installs_task = Some(tokio::spawn(async move {
+            let result = backend.installs(all_packages).await;
+            installs.get_or_init(|| result);
+            let _ = changes.send(());
+        }));
+    }
+
+    /// Switches to `view`, searching it for the same query and returning to where it was left.
+    ///
+    /// Everything but the leaves is already known once the first search is done, so this is
+    /// usually instant.
+    fn switch_view(&mut self, view: View) {
+        self.view_cursors[self.view.index()] = self.current;
+        self.view = view;
+        self.clear_info();
+        self.mark_dirty();
+
+        let (Some(all_packages), Some(installed)) = (self.all_packages.get(), self.installed.get())
+        else {
+            return;
+        };
+
+        if view == View::Leaves && self.leaves.get().is_none() {
+            self.current = 0;
+            self.shown.store(Shown::Few(Vec::new()).into());
+            self.search();
+            return;
+        }
+
+        let ctx = Context {
+            catalog: all_packages,
+            installed,
+            installs: self.installs.get(),
+            sizes: self.sizes.get(),
+            query: &self.searched,
+            leaves: self.leaves.get(),
+        };
+        let shown = search(&self.searched, &ctx, view, self.sort);
+        let len = shown.len().unwrap_or(all_packages.len());
+        if len == 0 {
+            *self.error_msg.lock() = Message::NoResults;
+        }
+        self.current = self.view_cursors[view.index()].min(len.saturating_sub(1));
+        self.shown.store(shown.into());
+    }
+
+    /// Every selected package, in catalog order.
+    pub fn basket_packages(&self) -> Vec<usize> {
+        let mut packages = self.selected.iter().copied().collect::<Vec<_>>();
+        packages.sort_unstable();
+        packages
+    }
+
+    /// The packages in the current view.
+    fn shown_indices(&self) -> Vec<usize> {
+        match (*self.shown).load().get_vec() {
+            Some(v) => v.clone(),
+            None => (0..self.shown_len()).collect(),
+        }
+    }
+
+    /// Starts loading the package list and searching for the query given on the command line.
+    pub fn start(&mut self) {
+        self.searched = self.query.text().to_owned();
+        self.search();
+    }
+
+    /// Runs the current query in the background, loading the package list first if needed.
+    fn search(&mut self) {
+        let query = self.searched.clone();
+        let view = self.view;
+        let sort = self.sort;
+        let mode = self.mode.clone();
+        let shown = self.shown.clone();
+        let error_msg = self.error_msg.clone();
+        let changes = self.changes.clone();
+        let all_packages = self.all_packages.clone();
+        let installed = self.installed.clone();
+        let installs = self.installs.clone();
+        let sizes = self.sizes.clone();
+        let leaves = self.leaves.clone();
+        let backend = self.backend.clone();
+
+        if let Some(task) = self.search_task.take() {
+            task.abort();
+        }
+        *self.error_msg.lock() = Message::TrySearch;
+
+        self.search_task = Some(tokio::spawn(async move {
+            let _ = changes.send(());
+
+            // Nothing is stored on failure, so that retrying starts over
+            let fail = |err: Error| {
+                log::error(&err);
+                *error_msg.lock() = Message::Error(err);
+                let _ = changes.send(());
+            };
+
+            if all_packages.get().is_none() {
+                match backend.list().await {
+                    Ok(result) => {
+                        all_packages.get_or_init(|| Box::leak(Box::new(result)));
+                    }
+                    Err(err) => return fail(err),
+                }
+            }
+
+            if installed.get().is_none() {
+                match backend.check_installed(all_packages.get().unwrap()).await {
+                    Ok(result) => {
+                        installed.get_or_init(|| result);
+                    }
+                    Err(err) => return fail(err),
+                }
+            }
+
+            let (all_packages, installed) = (all_packages.get().unwrap(), installed.get().unwrap());
+            if sort.key == SortKey::Size && sizes.get().is_none() {
+                let result = backend.sizes(all_packages, installed).await;
+                sizes.get_or_init(|| result);
+            }
+            if view == View::Leaves && leaves.get().is_none() {
+                let result = backend.leaves(all_packages).await;
+                leaves.get_or_init(|| result);
+            }
+
+            let ctx = Context {
+                catalog: all_packages,
+                installed,
+                installs: installs.get(),
+                sizes: sizes.get(),
+                query: &query,
+                leaves: leaves.get(),
+            };
+            shown.store(search(&query, &ctx, view, sort).into());
+
+            if !(*shown).load().is_empty() {
+                mode.store(Mode::Select, Ordering::Relaxed);
+            } else {
+                *error_msg.lock() = Message::NoResults;
+            }
+            let _ = changes.send(());
+        }));
+    }
+
+    /// Catches up on anything that changed in the background before a redraw.
+    pub fn update(&mut self) {
+        let source = self.source.lock().take();
+        match source {
+            Some((Action::EditSource, Ok(path))) => self.launch = Some(open::editor(&path)),
+            Some((_, Ok(path))) => self.outcome = Some(path.display().to_string()),
+            Some((_, Err(err))) => {
+                log::error(&err);
+                self.outcome = Some(format!("{err} — {}", err.hint()));
+            }
+            None => {}
+        }
+
+        // The terminal may have become too narrow for the info pane
+        if !self.panes.info_shown() {
+            self.focus = Focus::Results;
+        }
+
+        if self
+            .sizes_task
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            self.sizes_task = None;
+            self.resort();
+        }
+
+        if self
+            .installs_task
+            .as_ref()
+            .is_some_and(JoinHandle::is_finished)
+        {
+            self.installs_task = None;
+            self.resort();
+        }
+        self.fetch_installs();
+
+        if !self.preselect.is_empty() {
+            if let Some(all_packages) = self.all_packages.get() {
+                let preselect = std::mem::take(&mut self.preselect);
+                self.selected.extend(
+                    all_packages
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, p)| preselect.iter().any(|name| name == p))
+                        .map(|(i, _)| i),
+                );
+            }
+        }
+
+        let real_idx = self.real_idx(self.current);
+        let fetching = self
+            .info_task
+            .as_ref()
+            .is_some_and(|(i, t)| *i == real_idx && !t.is_finished());
+        if self.info.lock().is_empty() && !(*self.shown).load().is_empty() && !fetching {
+            let changes = self.changes.clone();
+            let info = self.info.clone();
+            let installed = self.installed.clone();
+            let all_packages = self.all_packages.clone();
+            let backend = self.backend.clone();
+
+            if let Some((_, task)) = self.info_task.take() {
+                task.abort();
+            }
+
+            let task = tokio::spawn(async move {
+                let mut newinfo = get_info(
+                    &*backend,
+                    all_packages.get().unwrap(),
+                    real_idx,
+                    installed.get().unwrap(),
+                )
+                .await;
+                // Otherwise it would be looked up again on every redraw
+                if newinfo.is_empty() {
+                    newinfo.push(Line::from("No info available"));
+                }
+                *info.lock() = newinfo;
+                let _ = changes.send(());
+            });
+            self.info_task = Some((real_idx, task));
+        }
+    }
+
+    pub fn handle_key(&mut self, event: KeyEvent) -> Option<Exit> {
+        let key = Key::from(event);
+
+        if self.help.is_some() {
+            return self.handle_help_key(key);
+        }
+
+        if self.prompt.is_some() {
+            return self.handle_prompt_key(key);
+        }
+
+        if self.basket.is_some() {
+            return self.handle_basket_key(key);
+        }
+
+        if self.reverse_search.is_some() {
+            if let Some(exit) = self.handle_reverse_search_key(key) {
+                return exit;
+            }
+        }
+
+        let mode = self.mode();
+        match self.keymap.get(mode, key) {
+            Some(action) => self.dispatch(action),
+            None => {
+                if let (Mode::Insert, KeyCode::Char(c)) = (mode, key.code) {
+                    if !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+                    {
+                        self.query.insert(c);
+                    }
+                }
+                self.mark_dirty();
+                None
+            }
+        }
+    }
+
+    fn handle_help_key(&mut self, key: Key) -> Option<Exit> {
+        let mode = self.help.as_ref()?.mode;
+        // Whichever key opened the overlay also closes it
+        let action = self.keymap.get(Mode::Help, key).or_else(|| {
+            self.keymap
+                .get(mode, key)
+                .filter(|&action| action == Action::Help)
+        });
+        let len = self.keymap.describe(mode).len() as u16;
+        let page = self.per_page as u16;
+        let help = self.help.as_mut()?;
+
+        match action {
+            Some(Action::Help) => self.help = None,
+            Some(Action::Down) => help.scroll += 1,
+            Some(Action::Up) => help.scroll = help.scroll.saturating_sub(1),
+            Some(Action::PageForward) => help.scroll += page,
+            Some(Action::PageBack) => help.scroll = help.scroll.saturating_sub(page),
+            Some(Action::Top) => help.scroll = 0,
+            Some(Action::Bottom) => help.scroll = len,
+            Some(action) => {
+                if let Some(exit) = self.dispatch(action) {
+                    return Some(exit);
+                }
+            }
+            None => {}
+        }
+
+        if let Some(help) = &mut self.help {
+            help.scroll = help.scroll.min(len.saturating_sub(1));
+        }
+
+        self.mark_dirty();
+        None
+    }
+
+    /// Forgets the info shown for the last package, along with how far it was scrolled and what
+    /// was searched for in it, so that the next package's info is fetched and starts at the top.
+    fn clear_info(&mut self) {
+        self.info.lock().clear();
+        self.info_scroll = 0;
+        self.info_search = None;
+    }
+
+    /// Scrolls the info pane by `lines`, stopping once its end is at the bottom.
+    fn scroll_info(&mut self, lines: i32) {
+        let layout = self.info_layout.borrow();
+        let max = layout.lines.saturating_sub(layout.height) as i32;
+        self.info_scroll = (self.info_scroll as i32)
+            .saturating_add(lines)
+            .clamp(0, max) as u16;
+        drop(layout);
+        self.mark_dirty();
+    }
+
+    /// How many lines of info fit in the pane.
+    fn info_page(&self) -> i32 {
+        self.info_layout.borrow().height.max(1) as i32
+    }
+
+    /// The lines of info containing `pattern`, ignoring ASCII case.
+    pub fn info_matches(&self, pattern: &str) -> Vec<usize> {
+        let pattern = pattern.to_ascii_lowercase();
+        self.info
+            .lock()
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                let text = line
+                    .spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>();
+                text.to_ascii_lowercase().contains(&pattern)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Scrolls the next line matching the info search to the top of the pane, wrapping around.
+    ///
+    /// A match already at the top only counts if `stay` is set.
+    fn jump_to_match(&mut self, forward: bool, stay: bool) {
+        let Some(pattern) = &self.info_search else {
+            return;
+        };
+        let matches = self.info_matches(pattern);
+        let layout = self.info_layout.borrow();
+        let start = |i: &usize| layout.starts.get(*i).copied().unwrap_or_default();
+
+        let scroll = self.info_scroll;
+        let found = if forward {
+            let mut after = matches
+                .iter()
+                .filter(|i| start(i) > scroll || stay && start(i) == scroll);
+            after.next().or(matches.first())
+        } else {
+            let mut before = matches.iter().rev().filter(|i| start(i) < scroll);
+            before.next().or(matches.last())
+        };
+        if let Some(i) = found {
+            self.info_scroll = start(i);
+        }
+    }
+
+    fn handle_basket_key(&mut self, key: Key) -> Option<Exit> {
+        let packages = self.basket_packages();
+        let basket = self.basket.as_mut()?;
+        let last = packages.len().saturating_sub(1);
+
+        match self.keymap.get(Mode::Basket, key) {
+            Some(Action::Basket) => self.basket = None,
+            Some(Action::Down) => basket.current += 1,
+            Some(Action::Up) => basket.current = basket.current.saturating_sub(1),
+            Some(Action::Top) => basket.current = 0,
+            Some(Action::Bottom) => basket.current = last,
+            Some(Action::ToggleSelect) => {
+                if let Some(package) = packages.get(basket.current) {
+                    self.selected.remove(package);
+                }
+            }
+            Some(Action::Install | Action::Remove | Action::Upgrade) if packages.is_empty() => {}
+            Some(action) => {
+                if let Some(exit) = self.dispatch(action) {
+                    return Some(exit);
+                }
+            }
+            None => {}
+        }
+
+        if let Some(basket) = &mut self.basket {
+            basket.current = basket.current.min(self.selected.len().saturating_sub(1));
+        }
+
+        self.mark_dirty();
+        None
+    }
+
+    fn handle_prompt_key(&mut self, key: Key) -> Option<Exit> {
+        let prompt = self.prompt.as_mut()?;
+        let input = &mut prompt.input;
+        let ctrl = key.modifiers == KeyModifiers::CONTROL;
+
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Some(Exit::Quit),
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => self.answer_prompt(),
+            KeyCode::Tab if prompt.kind != PromptKind::InfoSearch => {
+                let input = input.text().to_owned();
+                let matches = sets::list()
+                    .into_iter()
+                    .filter(|name| name.starts_with(&input))
+                    .collect::<Vec<_>>();
+                prompt.error = None;
+                match common_prefix(&matches) {
+                    Some(prefix) if prefix.len() > input.len() => {
+                        prompt.input.set_text(prefix.to_owned())
+                    }
+                    Some(_) if matches.len() > 1 => {
+                        prompt.error = Some(format!("Sets: {}", matches.join(", ")))
+                    }
+                    Some(_) => {}
+                    None => prompt.error = Some("No matching sets".to_owned()),
+                }
+            }
+            KeyCode::Backspace => input.delete_back(),
+            KeyCode::Delete => input.delete_forward(),
+            KeyCode::Left => input.left(),
+            KeyCode::Right => input.right(),
+            KeyCode::Home => input.start(),
+            KeyCode::Char('a') if ctrl => input.start(),
+            KeyCode::End => input.end(),
+            KeyCode::Char('e') if ctrl => input.end(),
+            KeyCode::Char('w') if ctrl => input.kill_word_back(),
+            KeyCode::Char('u') if ctrl => input.kill_to_start(),
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                input.insert(c)
+            }
+            _ => {}
+        }
+
+        self.mark_dirty();
+        None
+    }
+
+    /// Saves or loads the set named in the prompt, leaving the prompt open if that failed.
+    fn answer_prompt(&mut self) {
+        let Some(prompt) = &self.prompt else {
+            return;
+        };
+        let name = prompt.input.text().trim();
+
+        let result = match prompt.kind {
+            PromptKind::SaveSet => {
+                let all_packages = self.all_packages.get();
+                let packages = self
+                    .basket_packages()
+                    .into_iter()
+                    .filter_map(|i| all_packages.map(|p| &p[i]))
+                    .collect::<Vec<_>>();
+                if packages.is_empty() {
+                    Err("Nothing is selected".to_owned())
+                } else {
+                    sets::save(name, &packages).map(|()| {
+                        self.outcome = Some(format!(
+                            "Saved {} as `{name}`",
+                            plural(packages.len(), "package")
+                        ));
+                    })
+                }
+            }
+            PromptKind::LoadSet => sets::load(name).map(|mut packages| {
+                self.outcome = Some(format!(
+                    "Loaded {} from `{name}`",
+                    plural(packages.len(), "package")
+                ));
+                self.preselect.append(&mut packages);
+                self.basket = Some(Basket { current: 0 });
+            }),
+            PromptKind::InfoSearch if name.is_empty() => {
+                self.info_search = None;
+                Ok(())
+            }
+            PromptKind::InfoSearch => {
+                if self.info_matches(name).is_empty() {
+                    Err(format!("No match for `{name}`"))
+                } else {
+                    self.info_search = Some(name.to_owned());
+                    self.jump_to_match(true, true);
+                    Ok(())
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => self.prompt = None,
+            Err(err) => {
+                if let Some(prompt) = &mut self.prompt {
+                    prompt.error = Some(capitalize(&err));
+                }
+            }
+        }
+    }
+
+    pub fn handle_mouse(&mut self, event: MouseEvent, areas: Areas) {
+        if self.help.is_some() || self.basket.is_some() || self.prompt.is_some() {
+            return;
+        }
+
+        let position = Position {
+            x: event.column,
+            y: event.row,
+        };
+        let has_results = !(*self.shown).load().is_empty();
+        let over_info = has_results && areas.info.contains(position);
+
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left)
+                if has_results && areas.splitter.contains(position) =>
+            {
+                self.dragging = true;
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging && self.panes.info_shown() => {
+                // The splitter follows the pointer, as a share of the space both panes take up
+                let body = areas.results.union(areas.info);
+                let (offset, length) = match self.panes.info {
+                    InfoPosition::Right => (position.x.saturating_sub(body.x), body.width),
+                    InfoPosition::Below => (position.y.saturating_sub(body.y), body.height),
+                };
+                let split = u32::from(offset) * 100 / u32::from(length.max(1));
+                self.panes.resize(split as u16);
+                self.mark_dirty();
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragging = false,
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.reverse_search = None;
+                if areas.search.contains(position) {
+                    self.dispatch(Action::EnterInsert);
+                } else if over_info {
+                    self.focus = Focus::Info;
+                    if self.mode() == Mode::Insert {
+                        self.set_mode(Mode::Select);
+                    }
+                    self.mark_dirty();
+                } else if has_results && !over_info && areas.rows.contains(position) {
+                    self.focus = Focus::Results;
+                    let mut top = areas.rows.y;
+                    let clicked = self.result_rows.borrow().iter().find_map(|&(idx, height)| {
+                        top += height;
+                        (position.y < top).then_some(idx)
+                    });
+                    if let Some(idx) = clicked {
+                        self.click_row(idx);
+                    }
+                }
+            }
+            MouseEventKind::ScrollDown if over_info => {
+                self.dispatch(Action::InfoDown);
+            }
+            MouseEventKind::ScrollUp if over_info => {
+                self.dispatch(Action::InfoUp);
+            }
+            MouseEventKind::ScrollDown if areas.results.contains(position) => {
+                self.dispatch(Action::PageForward);
+            }
+            MouseEventKind::ScrollUp if areas.results.contains(position) => {
+                self.dispatch(Action::PageBack);
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves to the clicked row, toggling its selection if it was clicked twice in a row.
+    fn click_row(&mut self, idx: usize) {
+        let now = Instant::now();
+        let double = self
+            .last_click
+            .is_some_and(|(at, last)| last == idx && now.duration_since(at) < DOUBLE_CLICK);
+        self.last_click = (!double).then_some((now, idx));
+
+        if self.mode() == Mode::Insert {
+            self.set_mode(Mode::Select);
+        }
+        if self.current != idx {
+            self.current = idx;
+            self.clear_info();
+        }
+        if double {
+            self.dispatch(Action::ToggleSelect);
+        }
+        self.mark_dirty();
+    }
+
+    /// Handles a key while searching past queries, returning `None` when the search ended and
+    /// the key should go on to be handled normally, as readline does.
+    fn handle_reverse_search_key(&mut self, key: Key) -> Option<Option<Exit>> {
+        let search = self.reverse_search.as_mut()?;
+        let ctrl = key.modifiers == KeyModifiers::CONTROL;
+
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Some(Some(Exit::Quit)),
+            KeyCode::Char('r') if ctrl => {
+                if let Some(found) = self.history.find(&search.pattern, search.found) {
+                    search.found = Some(found);
+                }
+            }
+            KeyCode::Char('g') if ctrl => {
+                let original = std::mem::take(&mut search.original);
+                self.query.set_text(original);
+                self.reverse_search = None;
+            }
+            KeyCode::Esc => self.reverse_search = None,
+            KeyCode::Backspace => {
+                search.pattern.pop();
+                search.found = self.history.find(&search.pattern, None);
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                search.pattern.push(c);
+                search.found = self.history.find(&search.pattern, None);
+            }
+            _ => {
+                self.reverse_search = None;
+                return None;
+            }
+        }
+
+        if let Some(found) = self.reverse_search.as_ref().and_then(|s| s.found) {
+            self.query.set_text(self.history.get(found).to_owned());
+        }
+
+        self.mark_dirty();
+        Some(None)
+    }
+
+    fn dispatch(&mut self, action: Action) -> Option<Exit> {
+        // A view may have nothing in it, leaving no row to move from, or to act on unless some
+        // packages are selected
+        if (*self.shown).load().is_empty() {
+            let moves = matches!(
+                action,
+                Action::Up
+                    | Action::Down
+                    | Action::PageBack
+                    | Action::PageForward
+                    | Action::Top
+                    | Action::Bottom
+                    | Action::ToggleSelect
+                    | Action::EnterVisual
+            );
+            let acts = matches!(action, Action::Install | Action::Remove | Action::Upgrade);
+            if moves || acts && self.selected.is_empty() {
+                return None;
+            }
+        }
+
+        match action {
+            Action::Quit => return Some(Exit::Quit),
+            Action::Suspend => self.suspend = true,
+            Action::Help => {
+                self.help = Some(Help {
+                    mode: self.mode(),
+                    scroll: 0,
+                });
+                self.mark_dirty();
+            }
+            Action::EnterSelect if self.mode() == Mode::Visual => {
+                self.set_mode(Mode::Select);
+                self.mark_dirty();
+            }
+            Action::EnterSelect => {
+                if !(*self.shown).load().is_empty() {
+                    self.current = 0;
+                    self.mark_dirty();
+                    self.set_mode(Mode::Select);
+                }
+            }
+            Action::EnterInsert => {
+                self.query.end();
+                self.mark_dirty();
+                self.set_mode(Mode::Insert);
+            }
+            Action::Search => {
+                self.history.push(self.query.text());
+                self.clear_info();
+                self.current = 0;
+                self.view_cursors = [0; View::ALL.len()];
+                self.searched = self.query.text().to_owned();
+                self.mark_dirty();
+                self.shown.store(Shown::Few(Vec::new()).into());
+                self.outcome = None;
+                self.search();
+            }
+            Action::DeleteWord => {
+                self.query.kill_word_back();
+                self.mark_dirty();
+            }
+            Action::DeleteBack => {
+                self.query.delete_back();
+                self.mark_dirty();
+            }
+            Action::DeleteForward => {
+                self.query.delete_forward();
+                self.mark_dirty();
+            }
+            Action::KillToStart => {
+                self.query.kill_to_start();
+                self.mark_dirty();
+            }
+            Action::KillToEnd => {
+                self.query.kill_to_end();
+                self.mark_dirty();
+            }
+            Action::Yank => {
+                self.query.yank();
+                self.mark_dirty();
+            }
+            Action::CursorLeft => {
+                self.query.left();
+                self.mark_dirty();
+            }
+            Action::CursorRight => {
+                self.query.right();
+                self.mark_dirty();
+            }
+            Action::WordLeft => {
+                self.query.word_left();
+                self.mark_dirty();
+            }
+            Action::WordRight => {
+                self.query.word_right();
+                self.mark_dirty();
+            }
+            Action::CursorStart => {
+                self.query.start();
+                self.mark_dirty();
+            }
+            Action::CursorEnd => {
+                self.query.end();
+                self.mark_dirty();
+            }
+            Action::HistoryPrev => {
+                if let Some(query) = self.history.older(self.query.text()) {
+                    self.query.set_text(query.to_owned());
+                    self.mark_dirty();
+                }
+            }
+            Action::HistoryNext => {
+                if let Some(query) = self.history.newer() {
+                    self.query.set_text(query.to_owned());
+                    self.mark_dirty();
+                }
+            }
+            Action::ReverseSearch => {
+                self.reverse_search = Some(ReverseSearch {
+                    pattern: String::new(),
+                    found: None,
+                    original: self.query.text().to_owned(),
+                });
+                self.mark_dirty();
+            }
+            Action::Up => {
+                if self.current > 0 {
+                    self.current -= 1;
+                } else {
+                    self.current = self.shown_len() - 1;
+                }
+                self.clear_info();
+                self.mark_dirty();
+            }
+            Action::Down => {
+                let result_count = self.shown_len();
+
+                if result_count > 1 && self.current < result_count - 1 {
+                    self.current += 1;
+                } else {
+                    self.current = 0;
+                }
+                self.clear_info();
+                self.mark_dirty();
+            }
+            Action::InfoUp => self.scroll_info(-1),
+            Action::InfoDown => self.scroll_info(1),
+            Action::InfoHalfPageBack => self.scroll_info(-self.info_page() / 2),
+            Action::InfoHalfPageForward => self.scroll_info(self.info_page() / 2),
+            Action::InfoPageBack => self.scroll_info(-self.info_page()),
+            Action::InfoPageForward => self.scroll_info(self.info_page()),
+            Action::InfoTop => self.scroll_info(i32::MIN),
+            Action::InfoBottom => self.scroll_info(i32::MAX),
+            Action::InfoSearch => {
+                self.prompt = Some(Prompt::new(PromptKind::InfoSearch));
+                self.mark_dirty();
+            }
+            Action::InfoNextMatch => {
+                self.jump_to_match(true, false);
+                self.mark_dirty();
+            }
+            Action::InfoPrevMatch => {
+                self.jump_to_match(false, false);
+                self.mark_dirty();
+            }
+            Action::LeaveInfo => {
+                if self.info_search.take().is_none() {
+                    self.focus = Focus::Results;
+                }
+                self.mark_dirty();
+            }
+            Action::PageBack => {
+                let per_page = self.per_page;
+                let result_count = self.shown_len() - 1;
+                if result_count > per_page {
+                    if self.current >= per_page {
+                        self.current -= per_page;
+                    } else if self.current % per_page == 0 {
+                        self.current = result_count / per_page * per_page;
+                    } else {
+                        self.current = self.current / per_page * per_page;
+                    }
+                    self.clear_info();
+                    self.mark_dirty();
+                }
+            }
+            Action::PageForward => {
+                let per_page = self.per_page;
+                let shown_len = self.shown_len();
+
+                if shown_len > per_page {
+                    if self.current == shown_len - 1 {
+                        self.current = 0;
+                    } else if self.current + per_page > shown_len - 1 {
+                        self.current = shown_len - 1;
+                    } else {
+                        self.current += per_page;
+                    }
+                    self.clear_info();
+                    self.mark_dirty();
+                }
+            }
+            Action::Top => {
+                if self.current != 0 {
+                    self.clear_info();
+                    self.current = 0;
+                }
+                self.mark_dirty();
+            }
+            Action::Bottom => {
+                let last = self.shown_len() - 1;
+                if self.current != last {
+                    self.clear_info();
+                    self.current = last;
+                }
+                self.mark_dirty();
+            }
+            Action::ToggleSelect => {
+                let real_current = self.real_idx(self.current);
+                if !self.selected.remove(&real_current) {
+                    self.selected.insert(real_current);
+                }
+                self.mark_dirty();
+            }
+            Action::ClearSelection => {
+                self.selected.clear();
+                self.mark_dirty();
+            }
+            Action::Basket => {
+                self.basket = Some(Basket { current: 0 });
+                self.mark_dirty();
+            }
+            Action::SaveSet => {
+                self.prompt = Some(Prompt::new(PromptKind::SaveSet));
+                self.mark_dirty();
+            }
+            Action::LoadSet => {
+                self.prompt = Some(Prompt::new(PromptKind::LoadSet));
+                self.mark_dirty();
+            }
+            Action::SortNext | Action::SortReverse => {
+                if action == Action::SortReverse {
+                    self.sort.reverse = !self.sort.reverse;
+                } else {
+                    let keys = SortKey::ALL;
+                    let next = keys.iter().position(|&k| k == self.sort.key).unwrap_or(0) + 1;
+                    self.sort.key = keys[next % keys.len()];
+                    self.sort.reverse = false;
+                }
+                self.resort();
+            }
+            Action::FocusNext => {
+                self.focus = match self.focus {
+                    Focus::Results if self.panes.info_shown() => Focus::Info,
+                    _ => Focus::Results,
+                };
+                self.mark_dirty();
+            }
+            Action::GrowResults | Action::ShrinkResults => {
+                let delta = if action == Action::GrowResults { 5 } else { -5 };
+                self.panes
+                    .resize(self.panes.split.saturating_add_signed(delta));
+                self.mark_dirty();
+            }
+            Action::ToggleInfo => {
+                self.panes.info_hidden = !self.panes.info_hidden;
+                self.focus = Focus::Results;
+                if !self.panes.info_hidden && !self.panes.info_shown() {
+                    self.outcome =
+                        Some("No room for info on the right, press | to move it below".to_owned());
+                }
+                self.mark_dirty();
+            }
+            Action::FlipInfo => {
+                self.panes.info = match self.panes.info {
+                    InfoPosition::Right => InfoPosition::Below,
+                    InfoPosition::Below => InfoPosition::Right,
+                };
+                // In case it was the hint to do just this
+                self.outcome = None;
+                self.mark_dirty();
+            }
+            Action::Retry => {
+                let recoverable = matches!(
+                    &*self.error_msg.lock(),
+                    Message::Error(err) if err.is_recoverable()
+                );
+                if recoverable {
+                    self.outcome = None;
+                    self.search();
+                    self.mark_dirty();
+                }
+            }
+            Action::NextView | Action::PrevView => {
+                let offset = if action == Action::NextView { 1 } else { -1 };
+                self.switch_view(self.view.cycle(offset));
+            }
+            Action::EnterVisual => {
+                self.visual_anchor = self.current;
+                self.set_mode(Mode::Visual);
+                self.mark_dirty();
+            }
+            Action::SelectRange | Action::DeselectRange => {
+                for i in self.visual_range().unwrap_or_default() {
+                    if action == Action::SelectRange {
+                        self.selected.insert(i);
+                    } else {
+                        self.selected.remove(&i);
+                    }
+                }
+                self.set_mode(Mode::Select);
+                self.mark_dirty();
+            }
+            Action::SelectAll => {
+                self.selected.extend(self.shown_indices());
+                self.mark_dirty();
+            }
+            Action::InvertSelection => {
+                for i in self.shown_indices() {
+                    if !self.selected.remove(&i) {
+                        self.selected.insert(i);
+                    }
+                }
+                self.mark_dirty();
+            }
+            Action::SelectInstalled | Action::SelectOutdated => {
+                let (Some(all_packages), Some(installed)) =
+                    (self.all_packages.get(), self.installed.get())
+                else {
+                    return None;
+                };
+
+                let outdated_only = action == Action::SelectOutdated;
+                self.selected
+                    .extend(self.shown_indices().into_iter().filter(|&i| {
+                        if outdated_only {
+                            installed.is_outdated(all_packages, i)
+                        } else {
+                            installed.contains(&i)
+                        }
+                    }));
+                self.mark_dirty();
+            }
+            Action::OpenHomepage => {
+                if let (Some(all_packages), Some(i)) =
+                    (self.all_packages.get(), self.current_package())
+                {
+                    self.launch = Some(open::browser(&all_packages.homepage(i)));
+                    self.mark_dirty();
+                }
+            }
+            Action::EditSource | Action::ShowSource => self.find_source(action),
+            Action::Install => {
+                let packages = self.targets(false);
+                if packages.is_empty() {
+                    return None;
+                }
+                return Some(Exit::Run(Operation::Install, packages));
+            }
+            Action::Remove | Action::Upgrade => {
+                let packages = self.targets(true);
+                if packages.is_empty() {
+                    self.outcome = Some(if self.selected.is_empty() {
+                        "This package isn't installed".to_owned()
+                    } else {
+                        "None of the selected packages are installed".to_owned()
+                    });
+                    self.mark_dirty();
+                    return None;
+                }
+
+                let operation = if action == Action::Remove {
+                    Operation::Remove
+                } else {
+                    Operation::Upgrade
+                };
+                return Some(Exit::Run(operation, packages));
+            }
+        }
+
+        None
+    }
+
+    /// The package under the cursor, if there is one.
+    fn current_package(&self) -> Option<usize> {
+        self.all_packages.get()?;
+        if (*self.shown).load().is_empty() {
+            return None;
+        }
+        Some(self.real_idx(self.current))
+    }
+
+    /// Asks `brew` where the Ruby file of the current package is, for `action` to use once it
+    /// knows.
+    fn find_source(&mut self, action: Action) {
+        let (Some(all_packages), Some(i)) = (self.all_packages.get(), self.current_package())
+        else {
+            return;
+        };
+        let package = all_packages[i].to_owned();
+        let kind = all_packages.kind(i);
+        let backend = self.backend.clone();
+        let source = self.source.clone();
+        let changes = self.changes.clone();
+
+        if let Some(task) = self.source_task.take() {
+            task.abort();
+        }
+        self.source_task = Some(tokio::spawn(async move {
+            let result = backend.source(&package, kind).await;
+            *source.lock() = Some((action, result));
+            let _ = changes.send(());
+        }));
+        self.mark_dirty();
+    }
+
+    /// Notes how the program from [`App::launch`] went, once it has given the terminal back.
+    pub fn launched(&mut self, command: &Command, status: io::Result<ExitStatus>) {
+        let program = command.get_program().to_string_lossy();
+        self.outcome = match status {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(format!("`{program}` failed ({status})")),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Some(format!("`{program}` not found, set $BROWSER or $EDITOR"))
+            }
+            Err(err) => Some(format!("couldn't run `{program}` ({err})")),
+        };
+        self.mark_dirty();
+    }
+
+    /// The names of the selected packages, or the current one if none are selected.
+    fn targets(&self, installed_only: bool) -> Vec<String> {
+        let all_packages = self.all_packages.get().unwrap();
+        let installed = self.installed.get().unwrap();
+
+        let indices: Vec<usize> = if self.selected.is_empty() {
+            self.current_package().into_iter().collect()
+        } else {
+            self.selected.iter().copied().collect()
+        };
+
+        indices
+            .into_iter()
+            .filter(|i| !installed_only || installed.contains(i))
+            .map(|i| all_packages[i].to_owned())
+            .collect()
+    }
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        for task in [
+            self.search_task.take(),
+            self.info_task.take().map(|(_, t)| t),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            task.abort();
+        }
+    }
+}
+
+/// The longest prefix shared by every name, or `None` if there are none.
+fn common_prefix(names: &[String]) -> Option<&str> {
+    let (first, rest) = names.split_first()?;
+    let mut len = first.len();
+    for name in rest {
+        len = first
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or_default()
+            .min(len);
+    }
+    Some(&first[..len])
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use tokio::{sync::mpsc, time};
+
+    use super::*;
+    use crate::{backend::Fake, catalog::Period};
+
+    fn fake() -> Arc<Fake> {
+        Arc::new(Fake {
+            formulae: "ripgrep\t14.1.0\tSearch tool\n\
+                       fd\t10.1.0\tAlternative to find\n\
+                       bat\t0.24.0\tClone of cat\n"
+                .to_owned(),
+            installed: "fd\t10.1.0\n".to_owned(),
+            installs: "ripgrep\t3\t30\t300\n\
+                       fd\t9\t90\t900\n\
+                       bat\t5\t50\t500\n"
+                .to_owned(),
+            ..Fake::default()
+        })
+    }
+
+    /// Starts an app on `fake` with the default config, once the package list is loaded.
+    async fn start(fake: &Arc<Fake>) -> App {
+        // Nothing can be written under /dev/null, so the history and log stay untouched
+        env::set_var("XDG_STATE_HOME", "/dev/null");
+        let config = Config::new(["breweri", "--config", "/dev/null"].map(String::from));
+        let (changes, _) = mpsc::unbounded_channel();
+        let mut app = App::new(config, fake.clone(), changes);
+        app.start();
+        settle(&mut app).await;
+        app
+    }
+
+    /// Lets the background tasks run until nothing is left to wait for.
+    async fn settle(app: &mut App) {
+        while !app.activities().is_empty() {
+            time::sleep(Duration::from_millis(1)).await;
+        }
+        app.update();
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Option<Exit> {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn run(fake: &Fake, exit: Option<Exit>) {
+        let Some(Exit::Run(operation, packages)) = exit else {
+            panic!("expected to run brew");
+        };
+        let packages = packages.iter().map(String::as_str).collect::<Vec<_>>();
+        fake.run(operation, &packages).unwrap();
+    }
+
+    #[tokio::test]
+    async fn search_select_and_install() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        assert_eq!(app.shown_len(), 3);
+        assert!(app.mode() == Mode::Select);
+
+        press(&mut app, KeyCode::Esc);
+        for c in "grep".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        settle(&mut app).await;
+        assert_eq!(app.shown_len(), 1);
+        assert!(app.mode() == Mode::Select);
+
+        press(&mut app, KeyCode::Char(' '));
+        let exit = press(&mut app, KeyCode::Enter);
+        run(&fake, exit);
+        assert_eq!(*fake.commands.lock(), ["brew reinstall ripgrep"]);
+    }
+
+    #[tokio::test]
+    async fn install_counts_wait_until_sorted_by() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        assert!(app.installs.get().is_none());
+
+        app.sort.key = SortKey::Installs(Period::Month);
+        app.update();
+        settle(&mut app).await;
+        let all_packages = app.all_packages.get().unwrap();
+        let names = (0..3).map(|i| &all_packages[app.real_idx(i)]);
+        assert_eq!(names.collect::<Vec<_>>(), ["fd", "bat", "ripgrep"]);
+    }
+
+    #[tokio::test]
+    async fn moving_on_resets_the_info_pane() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        app.info_scroll = 5;
+        app.info_search = Some("search".to_owned());
+
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.info_scroll, 0);
+        assert!(app.info_search.is_none());
+
+        app.info_scroll = 5;
+        press(&mut app, KeyCode::Char(']'));
+        assert_eq!(app.info_scroll, 0);
+    }
+
+    #[tokio::test]
+    async fn help_follows_the_keymap() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        app.keymap.bind("select H help").unwrap();
+
+        press(&mut app, KeyCode::Char('H'));
+        assert!(app
+            .help
+            .as_ref()
+            .is_some_and(|help| help.mode == Mode::Select));
+        press(&mut app, KeyCode::Char('H'));
+        assert!(app.help.is_none());
+
+        // The select mode keys info mode falls back to are listed along with its own
+        let info = app.keymap.describe(Mode::Info);
+        let install = Action::Install.description();
+        assert!(info
+            .iter()
+            .any(|(keys, what)| keys == "<Return>" && *what == install));
+    }
+
+    #[tokio::test]
+    async fn nothing_runs_from_an_empty_view() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+
+        // Nothing is outdated, so the Outdated view is empty
+        press(&mut app, KeyCode::Char(']'));
+        press(&mut app, KeyCode::Char(']'));
+        settle(&mut app).await;
+        assert!(app.view == View::Outdated);
+        assert_eq!(app.shown_len(), 0);
+
+        for code in [KeyCode::Enter, KeyCode::Char('R'), KeyCode::Char('U')] {
+            assert!(press(&mut app, code).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn selections_install_from_an_empty_view() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        press(&mut app, KeyCode::Char(' '));
+
+        // There are no casks, so the Casks view is empty
+        for _ in 0..3 {
+            press(&mut app, KeyCode::Char(']'));
+        }
+        settle(&mut app).await;
+        assert!(app.view == View::Casks);
+        assert_eq!(app.shown_len(), 0);
+
+        press(&mut app, KeyCode::Char('b'));
+        let exit = press(&mut app, KeyCode::Enter);
+        run(&fake, exit);
+        assert_eq!(*fake.commands.lock(), ["brew reinstall ripgrep"]);
+    }
+
+    #[tokio::test]
+    async fn info_keys_scroll_within_the_pane() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        *app.info_layout.borrow_mut() = InfoLayout {
+            starts: (0..10).collect(),
+            lines: 10,
+            height: 4,
+        };
+
+        press(&mut app, KeyCode::Tab);
+        assert!(app.mode() == Mode::Info);
+        for _ in 0..10 {
+            press(&mut app, KeyCode::Char('j'));
+        }
+        assert_eq!(app.info_scroll, 6);
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.info_scroll, 0);
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.mode() == Mode::Select);
+        for _ in 0..10 {
+            app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL));
+        }
+        assert_eq!(app.info_scroll, 6);
+    }
+
+    #[tokio::test]
+    async fn basket_keys_follow_the_keymap() {
+        let fake = fake();
+        let mut app = start(&fake).await;
+        app.keymap.bind("basket <CR> none").unwrap();
+        app.keymap.bind("basket u upgrade").unwrap();
+
+        // fd is the only installed package, and comes second in catalog order
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char('b'));
+        assert!(app.mode() == Mode::Basket);
+
+        assert!(press(&mut app, KeyCode::Enter).is_none());
+        let exit = press(&mut app, KeyCode::Char('u'));
+        run(&fake, exit);
+        assert_eq!(*fake.commands.lock(), ["brew upgrade fd"]);
+    }
+}
@@ -0,0 +1,91 @@
+use std::{future::Future, io, path::PathBuf, pin::Pin, process::exit, sync::Arc};
+
+use crate::{
+    catalog::{Catalog, Installs, Kind},
+    config::Config,
+    error::Error,
+    installed::{Installed, Leaves, Sizes},
+};
+
+pub use self::brew::Brew;
+#[cfg(test)]
+pub use self::fake::Fake;
+
+mod brew;
+#[cfg(test)]
+mod fake;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Install,
+    Remove,
+    Upgrade,
+}
+
+impl Operation {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Install => "reinstall",
+            Operation::Remove => "remove",
+            Operation::Upgrade => "upgrade",
+        }
+    }
+}
+
+/// Everything breweri needs from Homebrew.
+///
+/// The production implementation is [`Brew`], while tests use an in-memory `Fake` so that the
+/// interface can be driven without Homebrew or a network connection.
+pub trait Backend: Send + Sync {
+    /// Lists every formula and cask.
+    fn list(&self) -> BoxFuture<'_, Result<Catalog, Error>>;
+
+    /// Finds which of `packages` are installed, and at which versions.
+    fn check_installed<'a>(
+        &'a self,
+        packages: &'a Catalog,
+    ) -> BoxFuture<'a, Result<Installed, Error>>;
+
+    /// Finds how often each of `packages` was installed recently. Analytics are only nice to
+    /// have, so any that can't be had are left at zero.
+    fn installs<'a>(&'a self, packages: &'a Catalog) -> BoxFuture<'a, Installs>;
+
+    /// Measures how much disk space each of the `installed` packages takes up.
+    fn sizes<'a>(&'a self, packages: &'a Catalog, installed: &'a Installed)
+        -> BoxFuture<'a, Sizes>;
+
+    /// Finds the installed formulae that no other installed formula depends on.
+    fn leaves<'a>(&'a self, packages: &'a Catalog) -> BoxFuture<'a, Leaves>;
+
+    /// Returns the raw `brew info` output for `package`.
+    fn info<'a>(&'a self, package: &'a str) -> BoxFuture<'a, String>;
+
+    /// Finds the Ruby file `package` is defined in, within its tap.
+    fn source<'a>(&'a self, package: &'a str, kind: Kind) -> BoxFuture<'a, Result<PathBuf, Error>>;
+
+    /// Runs `operation` on `packages`.
+    ///
+    /// This replaces the current process where possible, so it should only be called after the
+    /// terminal has been restored.
+    fn run(&self, operation: Operation, packages: &[&str]) -> io::Result<()>;
+}
+
+/// Sets up the [`Brew`] backend, exiting if `brew` cannot be run.
+pub fn from_config(config: &Config) -> Arc<dyn Backend> {
+    match Brew::new(config.brew.clone(), config.env.clone(), config.offline) {
+        Ok(brew) => Arc::new(brew),
+        Err(err) => {
+            match err.kind() {
+                io::ErrorKind::NotFound => {
+                    eprintln!("breweri: {} not found", config.brew.display());
+                }
+                _ => {
+                    eprintln!("breweri: {err}");
+                }
+            }
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs, io,
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{Output, Stdio},
+};
+
+use tokio::{
+    join,
+    process::{Child, Command},
+    task,
+};
+
+use super::{Backend, BoxFuture, Operation};
+use crate::{
+    catalog::{Catalog, Installs, Kind, Period},
+    dirs,
+    error::{Error, ErrorKind},
+    installed::{Installed, Keg, Leaves, Sizes},
+    log,
+};
+
+/// Talks to the real `brew`, using `curl` and `jq` to fetch the catalog.
+pub struct Brew {
+    path: PathBuf,
+    prefix: PathBuf,
+    env: Vec<(String, String)>,
+    offline: bool,
+}
+
+impl Brew {
+    /// Asks the `brew` at `path` for its prefix, which also checks that it can be run at all.
+    ///
+    /// `env` is only passed to actions, as lookups should not be affected by it. If `offline` is
+    /// set, the catalog is read from the copy cached by the last successful download.
+    pub fn new(path: PathBuf, env: Vec<(String, String)>, offline: bool) -> io::Result<Self> {
+        let output = std::process::Command::new(&path).arg("--prefix").output()?;
+        if !output.status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{} --prefix failed", path.display()),
+            ));
+        }
+
+        let prefix = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+        Ok(Self {
+            path,
+            prefix: PathBuf::from(prefix),
+            env,
+            offline,
+        })
+    }
+}
+
+impl Backend for Brew {
+    fn list(&self) -> BoxFuture<'_, Result<Catalog, Error>> {
+        Box::pin(list(self.offline))
+    }
+
+    fn check_installed<'a>(
+        &'a self,
+        packages: &'a Catalog,
+    ) -> BoxFuture<'a, Result<Installed, Error>> {
+        Box::pin(async move { check_installed(&self.prefix, packages) })
+    }
+
+    fn installs<'a>(&'a self, packages: &'a Catalog) -> BoxFuture<'a, Installs> {
+        Box::pin(installs(packages, self.offline))
+    }
+
+    fn sizes<'a>(
+        &'a self,
+        packages: &'a Catalog,
+        installed: &'a Installed,
+    ) -> BoxFuture<'a, Sizes> {
+        let kegs = package_dirs(&self.prefix, packages, installed);
+        Box::pin(async move {
+            // Walking every keg blocks on the file system, so it is kept off the async workers
+            let walk = move || {
+                kegs.into_iter()
+                    .map(|(i, dir)| (i, dir_size(&dir)))
+                    .collect()
+            };
+            task::spawn_blocking(walk).await.unwrap_or_default()
+        })
+    }
+
+    fn leaves<'a>(&'a self, packages: &'a Catalog) -> BoxFuture<'a, Leaves> {
+        Box::pin(async move {
+            let mut cmd = Command::new(&self.path);
+            cmd.arg("leaves");
+            let names = cmd_output(cmd).await;
+            packages.find_all(names.lines())
+        })
+    }
+
+    fn info<'a>(&'a self, package: &'a str) -> BoxFuture<'a, String> {
+        Box::pin(async move {
+            let mut cmd = Command::new(&self.path);
+            cmd.arg("info").arg(package);
+            cmd_output(cmd).await
+        })
+    }
+
+    fn source<'a>(&'a self, package: &'a str, kind: Kind) -> BoxFuture<'a, Result<PathBuf, Error>> {
+        Box::pin(async move {
+            let mut cmd = Command::new(&self.path);
+            cmd.args(["edit", "--print-path"]);
+            if kind == Kind::Cask {
+                cmd.arg("--cask");
+            }
+            let output = cmd
+                .arg(package)
+                .output()
+                .await
+                .map_err(|err| Error::new(ErrorKind::Source, err.to_string()))?;
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match stdout.trim() {
+                path if output.status.success() && !path.is_empty() => Ok(PathBuf::from(path)),
+                _ => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let detail = stderr.trim().trim_start_matches("Error: ");
+                    Err(Error::new(ErrorKind::Source, detail))
+                }
+            }
+        })
+    }
+
+    fn run(&self, operation: Operation, packages: &[&str]) -> io::Result<()> {
+        let mut cmd = std::process::Command::new(&self.path);
+        cmd.envs(self.env.iter().map(|(k, v)| (OsStr::new(k), OsStr::new(v))));
+        cmd.arg(operation.as_str());
+        cmd.args(packages);
+        Err(cmd.exec())
+    }
+}
+
+async fn list(offline: bool) -> Result<Catalog, Error> {
+    let cache = dirs::cache_dir();
+
+    if offline {
+        let read = |name: &str| cache.as_ref().and_then(|dir| fs::read(dir.join(name)).ok());
+        let (Some(formulae), Some(casks)) = (read("formulae"), read("casks")) else {
+            return Err(Error::new(ErrorKind::NoCache, ""));
+        };
+        return Ok(Catalog::parse(&formulae, &casks));
+    }
+
+    let formulae = fetch(
+        "https://formulae.brew.sh/api/formula.json",
+        Catalog::FORMULAE_FILTER,
+    )?;
+    let casks = fetch(
+        "https://formulae.brew.sh/api/cask.json",
+        Catalog::CASKS_FILTER,
+    )?;
+    let (formulae, casks) = join!(formulae.output(), casks.output());
+    let (formulae, casks) = (formulae?, casks?);
+
+    // Keep a copy around for --offline
+    if let Some(dir) = cache {
+        if fs::create_dir_all(&dir).is_ok() {
+            let _ = fs::write(dir.join("formulae"), &formulae);
+            let _ = fs::write(dir.join("casks"), &casks);
+        }
+    }
+
+    Ok(Catalog::parse(&formulae, &casks))
+}
+
+async fn installs(packages: &Catalog, offline: bool) -> Installs {
+    let cache = dirs::cache_dir();
+    let mut installs = Installs::new(packages);
+
+    if offline {
+        for period in Period::ALL {
+            let name = format!("installs-{}", period.as_str());
+            let lines = cache.as_ref().and_then(|dir| fs::read(dir.join(name)).ok());
+            installs.set(packages, period, &lines.unwrap_or_default());
+        }
+        return installs;
+    }
+
+    let fetch_period = |period: Period| async move {
+        let fetch_installs = |kind| async move {
+            let url = format!(
+                "https://formulae.brew.sh/api/analytics/{kind}/{}.json",
+                period.as_str()
+            );
+            let result = match fetch(&url, Installs::FILTER) {
+                Ok(fetch) => fetch.output().await,
+                Err(err) => Err(err),
+            };
+            result.map_err(|err| log::error(&err)).ok()
+        };
+        let (formulae, casks) = join!(fetch_installs("install"), fetch_installs("cask-install"));
+        formulae.zip(casks).map(|(mut formulae, casks)| {
+            formulae.extend(casks);
+            (period, formulae)
+        })
+    };
+
+    let (month, quarter, year) = join!(
+        fetch_period(Period::Month),
+        fetch_period(Period::Quarter),
+        fetch_period(Period::Year),
+    );
+
+    for (period, lines) in [month, quarter, year].into_iter().flatten() {
+        // Keep a copy around for --offline
+        if let Some(dir) = &cache {
+            if fs::create_dir_all(dir).is_ok() {
+                let _ = fs::write(dir.join(format!("installs-{}", period.as_str())), &lines);
+            }
+        }
+        installs.set(packages, period, &lines);
+    }
+    installs
+}
+
+/// A download from `curl` being piped through `jq`.
+struct Fetch {
+    curl: Child,
+    jq: Child,
+}
+
+/// Starts piping the JSON at `url` through `jq` with `filter`.
+fn fetch(url: &str, filter: &str) -> Result<Fetch, Error> {
+    let spawn_error = |program, err: io::Error| match err.kind() {
+        io::ErrorKind::NotFound => Error::new(ErrorKind::Missing(program), ""),
+        _ => Error::new(ErrorKind::Network, format!("{program}: {err}")),
+    };
+
+    let mut curl = Command::new("curl")
+        .arg("-sSf")
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| spawn_error("curl", err))?;
+
+    let stdout = curl
+        .stdout
+        .take()
+        .ok_or_else(|| Error::new(ErrorKind::Network, "curl has no stdout"))?
+        .into_owned_fd()
+        .map_err(|err| spawn_error("curl", err))?;
+
+    let jq = Command::new("jq")
+        .arg("-r")
+        .arg(filter)
+        .stdin(Stdio::from(stdout))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| spawn_error("jq", err))?;
+
+    Ok(Fetch { curl, jq })
+}
+
+impl Fetch {
+    /// Waits for the filtered JSON, failing if either `curl` or `jq` did.
+    async fn output(self) -> Result<Vec<u8>, Error> {
+        let (curl, jq) = join!(self.curl.wait_with_output(), self.jq.wait_with_output());
+        let curl = curl.map_err(|err| Error::new(ErrorKind::Network, err.to_string()))?;
+        let jq = jq.map_err(|err| Error::new(ErrorKind::Jq, err.to_string()))?;
+
+        // curl is checked first, as jq is bound to fail on whatever it got
+        let stderr = |output: &Output| String::from_utf8_lossy(&output.stderr).trim().to_owned();
+        if !curl.status.success() {
+            return Err(Error::new(ErrorKind::Network, stderr(&curl)));
+        }
+        if !jq.status.success() {
+            return Err(Error::new(ErrorKind::Jq, stderr(&jq)));
+        }
+        Ok(jq.stdout)
+    }
+}
+
+fn check_installed(prefix: &Path, packages: &Catalog) -> Result<Installed, Error> {
+    let mut found = HashMap::new();
+
+    for dir in [prefix.join("Cellar"), prefix.join("Caskroom")] {
+        let names = match subdirs(&dir) {
+            Ok(names) => names,
+            // Casks are not supported on Linux, so either of these may be missing
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => {
+                let detail = format!("{}: {err}", dir.display());
+                return Err(Error::new(ErrorKind::Cellar, detail));
+            }
+        };
+
+        for name in names {
+            let package = dir.join(&name);
+            let kegs = subdirs(&package)
+                .into_iter()
+                .flatten()
+                .map(|version| Keg {
+                    installed_at: fs::metadata(package.join(&version))
+                        .and_then(|m| m.modified())
+                        .ok(),
+                    version,
+                })
+                .collect();
+            found.insert(name, kegs);
+        }
+    }
+
+    Ok(Installed::new(packages, found))
+}
+
+/// Where each of the `installed` packages keeps its kegs.
+fn package_dirs(prefix: &Path, packages: &Catalog, installed: &Installed) -> Vec<(usize, PathBuf)> {
+    installed
+        .iter()
+        .map(|i| {
+            let dir = match packages.kind(i) {
+                Kind::Formula => prefix.join("Cellar"),
+                Kind::Cask => prefix.join("Caskroom"),
+            };
+            (i, dir.join(&packages[i]))
+        })
+        .collect()
+}
+
+/// Adds up the sizes of the files under `path`, without following symlinks.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| dir_size(&entry.path()))
+        .sum()
+}
+
+/// Lists the names of the directories in `dir`, skipping hidden ones such as `.metadata`.
+fn subdirs(dir: &Path) -> io::Result<impl Iterator<Item = String>> {
+    Ok(fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|ft| ft.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.')))
+}
+
+async fn cmd_output(mut cmd: Command) -> String {
+    cmd.output()
+        .await
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default()
+}
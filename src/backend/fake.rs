@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use parking_lot::Mutex;
+
+use super::{Backend, BoxFuture, Operation};
+use crate::{
+    catalog::{Catalog, Installs, Kind, Period},
+    error::{Error, ErrorKind},
+    installed::{Installed, Keg, Leaves, Sizes},
+};
+
+/// Serves in-memory fixtures instead of talking to Homebrew, so tests can drive the interface.
+///
+/// Every field is optional:
+///  - `formulae` and `casks`: one package per line, as produced by the filters in [`Catalog`]
+///  - `installs`: a package name and its install counts over 30, 90 and 365 days per line
+///  - `installed`: one installed package per line, optionally followed by its version, when it
+///    was installed in seconds since the epoch, and its size in bytes, all tab-separated
+///  - `leaves`: one package per line, as `brew leaves` would list them
+///  - `info`: what `brew info <name>` should print, by name
+///  - `sources`: the Ruby file `brew edit` should open, by name
+///
+/// Every install, remove and upgrade the fake is asked to run is recorded in `commands` as a
+/// `brew` command line, so tests can assert on what breweri would have done.
+#[derive(Default)]
+pub struct Fake {
+    pub formulae: String,
+    pub casks: String,
+    pub installs: String,
+    pub installed: String,
+    pub leaves: String,
+    pub info: HashMap<String, String>,
+    pub sources: HashMap<String, PathBuf>,
+    pub commands: Mutex<Vec<String>>,
+}
+
+impl Fake {
+    /// The tab-separated fields of each line of `installed`.
+    fn installed(&self) -> impl Iterator<Item = Vec<&str>> {
+        self.installed
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|line| line.split('\t').collect())
+    }
+}
+
+impl Backend for Fake {
+    fn list(&self) -> BoxFuture<'_, Result<Catalog, Error>> {
+        Box::pin(async move {
+            Ok(Catalog::parse(
+                self.formulae.as_bytes(),
+                self.casks.as_bytes(),
+            ))
+        })
+    }
+
+    fn installs<'a>(&'a self, packages: &'a Catalog) -> BoxFuture<'a, Installs> {
+        Box::pin(async move {
+            let mut installs = Installs::new(packages);
+            // Each line has a name followed by installs over every period, longest last
+            for period in Period::ALL {
+                let lines = self
+                    .installs
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.split('\t');
+                        let name = fields.next()?;
+                        let count = fields.nth(period as usize)?;
+                        Some(format!("{name}\t{count}\n"))
+                    })
+                    .collect::<String>();
+                installs.set(packages, period, lines.as_bytes());
+            }
+            installs
+        })
+    }
+
+    fn check_installed<'a>(
+        &'a self,
+        packages: &'a Catalog,
+    ) -> BoxFuture<'a, Result<Installed, Error>> {
+        Box::pin(async move {
+            let found = self
+                .installed()
+                .map(|fields| {
+                    let kegs = fields
+                        .get(1)
+                        .map(|version| Keg {
+                            version: version.to_string(),
+                            installed_at: fields
+                                .get(2)
+                                .and_then(|secs| secs.parse().ok())
+                                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                        })
+                        .into_iter()
+                        .collect();
+                    (fields[0].to_owned(), kegs)
+                })
+                .collect();
+
+            Ok(Installed::new(packages, found))
+        })
+    }
+
+    fn sizes<'a>(
+        &'a self,
+        packages: &'a Catalog,
+        installed: &'a Installed,
+    ) -> BoxFuture<'a, Sizes> {
+        Box::pin(async move {
+            let sizes = self
+                .installed()
+                .filter_map(|fields| Some((fields[0], fields.get(3)?.parse().ok()?)))
+                .collect::<HashMap<&str, u64>>();
+
+            installed
+                .iter()
+                .filter_map(|i| Some((i, *sizes.get(&packages[i])?)))
+                .collect()
+        })
+    }
+
+    fn leaves<'a>(&'a self, packages: &'a Catalog) -> BoxFuture<'a, Leaves> {
+        Box::pin(async move { packages.find_all(self.leaves.lines()) })
+    }
+
+    fn info<'a>(&'a self, package: &'a str) -> BoxFuture<'a, String> {
+        Box::pin(async move { self.info.get(package).cloned().unwrap_or_default() })
+    }
+
+    fn source<'a>(&'a self, package: &'a str, _: Kind) -> BoxFuture<'a, Result<PathBuf, Error>> {
+        Box::pin(async move {
+            self.sources
+                .get(package)
+                .cloned()
+                .ok_or_else(|| Error::new(ErrorKind::Source, format!("{package} isn't tapped")))
+        })
+    }
+
+    fn run(&self, operation: Operation, packages: &[&str]) -> io::Result<()> {
+        let mut args = vec![operation.as_str()];
+        args.extend_from_slice(packages);
+        self.commands
+            .lock()
+            .push(format!("brew {}", args.join(" ")));
+        Ok(())
+    }
+}
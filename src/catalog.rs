@@ -0,0 +1,215 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Index,
+};
+
+use compact_strings::CompactStrings;
+use nohash_hasher::IntSet;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    Formula,
+    Cask,
+}
+
+impl Kind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Formula => "formula",
+            Kind::Cask => "cask",
+        }
+    }
+}
+
+/// A window of Homebrew's install analytics.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    pub const ALL: [Period; 3] = [Period::Month, Period::Quarter, Period::Year];
+
+    /// How the period is named in analytics URLs.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Period::Month => "30d",
+            Period::Quarter => "90d",
+            Period::Year => "365d",
+        }
+    }
+}
+
+/// Every formula and cask Homebrew knows about, formulae first.
+///
+/// Packages are referred to by their index in here everywhere else.
+pub struct Catalog {
+    names: CompactStrings,
+    versions: CompactStrings,
+    descriptions: CompactStrings,
+    homepages: CompactStrings,
+    casks_start: usize,
+}
+
+impl Catalog {
+    /// The `jq` filter that turns `formula.json` into lines [`Catalog::parse`] understands.
+    ///
+    /// Revisions are appended the same way Homebrew names kegs, so they can be compared with
+    /// what is in the Cellar.
+    pub const FORMULAE_FILTER: &'static str = concat!(
+        ".[]|[.name, (.versions.stable // \"\") + ",
+        "(if (.revision // 0) > 0 then \"_\\(.revision)\" else \"\" end), .desc // \"\", ",
+        ".homepage // \"\"]|@tsv"
+    );
+    /// The `jq` filter that turns `cask.json` into lines [`Catalog::parse`] understands.
+    pub const CASKS_FILTER: &'static str =
+        ".[]|[.full_token, .version // \"\", .desc // \"\", .homepage // \"\"]|@tsv";
+    /// Parses tab-separated `name version description homepage` lines, skipping any that are not
+    /// valid UTF-8.
+    pub fn parse(formulae: &[u8], casks: &[u8]) -> Self {
+        let mut catalog = Self {
+            names: CompactStrings::with_capacity(16 * 16384, 16384),
+            versions: CompactStrings::with_capacity(8 * 16384, 16384),
+            descriptions: CompactStrings::with_capacity(48 * 16384, 16384),
+            homepages: CompactStrings::with_capacity(40 * 16384, 16384),
+            casks_start: 0,
+        };
+
+        catalog.extend(formulae);
+        catalog.casks_start = catalog.len();
+        catalog.extend(casks);
+
+        catalog.names.shrink_to_fit();
+        catalog.names.shrink_meta_to_fit();
+        catalog.versions.shrink_to_fit();
+        catalog.versions.shrink_meta_to_fit();
+        catalog.descriptions.shrink_to_fit();
+        catalog.descriptions.shrink_meta_to_fit();
+        catalog.homepages.shrink_to_fit();
+        catalog.homepages.shrink_meta_to_fit();
+
+        catalog
+    }
+
+    fn extend(&mut self, lines: &[u8]) {
+        for line in lines.split(|b| *b == b'\n') {
+            let Ok(line) = std::str::from_utf8(line) else {
+                continue;
+            };
+
+            let mut fields = line.split('\t');
+            let name = fields.next().unwrap_or_default();
+            if name.is_empty() {
+                continue;
+            }
+
+            self.names.push(name);
+            self.versions.push(fields.next().unwrap_or_default());
+            self.descriptions.push(fields.next().unwrap_or_default());
+            self.homepages.push(fields.next().unwrap_or_default());
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// The indices of the packages named in `names`, ignoring unknown ones.
+    pub fn find_all<'a>(&self, names: impl Iterator<Item = &'a str>) -> IntSet<usize> {
+        let names = names.map(str::trim).collect::<HashSet<_>>();
+        self.iter()
+            .enumerate()
+            .filter(|(_, name)| names.contains(name))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter()
+    }
+
+    pub fn kind(&self, index: usize) -> Kind {
+        if index < self.casks_start {
+            Kind::Formula
+        } else {
+            Kind::Cask
+        }
+    }
+
+    /// The latest version of the package at `index`, which may be empty if it is unknown.
+    pub fn version(&self, index: usize) -> &str {
+        &self.versions[index]
+    }
+
+    /// A one-line summary of the package at `index`, which may be empty.
+    pub fn description(&self, index: usize) -> &str {
+        &self.descriptions[index]
+    }
+
+    /// Where the package at `index` lives online, falling back to its page on formulae.brew.sh.
+    pub fn homepage(&self, index: usize) -> String {
+        match &self.homepages[index] {
+            "" => format!(
+                "https://formulae.brew.sh/{}/{}",
+                self.kind(index).as_str(),
+                &self[index]
+            ),
+            homepage => homepage.to_owned(),
+        }
+    }
+}
+
+impl Index<usize> for Catalog {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+}
+
+/// How often each package in a [`Catalog`] was installed over each [`Period`], from Homebrew's
+/// analytics.
+///
+/// These take several downloads, so they are only fetched once a sort or column needs them.
+pub struct Installs([Vec<u32>; 3]);
+
+impl Installs {
+    /// The `jq` filter that turns the install analytics for formulae or casks into lines
+    /// [`Installs::set`] understands.
+    pub const FILTER: &'static str = ".items[]|[.formula // .cask, (.count|gsub(\",\";\"\"))]|@tsv";
+
+    /// No installs for any of the packages in `catalog`.
+    pub fn new(catalog: &Catalog) -> Self {
+        Self(Period::ALL.map(|_| vec![0; catalog.len()]))
+    }
+
+    /// Reads tab-separated `name count` lines of install analytics over `period`, ignoring
+    /// names that aren't in `catalog`.
+    pub fn set(&mut self, catalog: &Catalog, period: Period, lines: &[u8]) {
+        let indices = catalog
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name, i))
+            .collect::<HashMap<_, _>>();
+
+        for line in lines.split(|b| *b == b'\n') {
+            let Ok(line) = std::str::from_utf8(line) else {
+                continue;
+            };
+
+            let Some((name, count)) = line.split_once('\t') else {
+                continue;
+            };
+            if let (Some(&i), Ok(count)) = (indices.get(name), count.parse::<u32>()) {
+                let installs = &mut self.0[period as usize][i];
+                *installs = installs.saturating_add(count);
+            }
+        }
+    }
+
+    pub fn get(&self, period: Period, index: usize) -> u32 {
+        self.0[period as usize][index]
+    }
+}
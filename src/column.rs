@@ -0,0 +1,88 @@
+use crate::sort::{Sort, SortKey};
+
+/// Something that can be shown about each package in the results table.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Index,
+    Name,
+    Kind,
+    Installed,
+    Latest,
+    Installs,
+    Description,
+}
+
+impl Column {
+    pub const ALL: &'static [Column] = &[
+        Column::Index,
+        Column::Name,
+        Column::Kind,
+        Column::Installed,
+        Column::Latest,
+        Column::Installs,
+        Column::Description,
+    ];
+
+    pub const DEFAULT: &'static [Column] = &[
+        Column::Index,
+        Column::Name,
+        Column::Installed,
+        Column::Latest,
+        Column::Description,
+    ];
+
+    /// The name used for this column in the `columns` config key.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Column::Index => "index",
+            Column::Name => "name",
+            Column::Kind => "kind",
+            Column::Installed => "installed",
+            Column::Latest => "latest",
+            Column::Installs => "installs",
+            Column::Description => "description",
+        }
+    }
+
+    pub const fn title(&self) -> &'static str {
+        match self {
+            Column::Index => "#",
+            Column::Name => "Name",
+            Column::Kind => "Kind",
+            Column::Installed => "Installed",
+            Column::Latest => "Latest",
+            Column::Installs => "Installs",
+            Column::Description => "Description",
+        }
+    }
+
+    /// Parses a comma-separated list of column names.
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .find(|c| c.name() == name)
+                    .ok_or_else(|| format!("unknown column `{name}`"))
+            })
+            .collect()
+    }
+
+    /// The sort key that orders packages by this column, which for installs is whichever period
+    /// `sort` is showing.
+    pub fn sort_key(&self, sort: Sort) -> SortKey {
+        match self {
+            Column::Index => SortKey::Catalog,
+            Column::Name => SortKey::Name,
+            Column::Kind => SortKey::Kind,
+            Column::Installed => SortKey::Installed,
+            Column::Latest => SortKey::Latest,
+            Column::Installs => SortKey::Installs(sort.key.period()),
+            Column::Description => SortKey::Description,
+        }
+    }
+}
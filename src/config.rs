@@ -1,43 +1,213 @@
-use std::{env::Args, process::exit};
+use std::{env, path::PathBuf, process::exit};
 
-use self::help::print_help;
+use self::{
+    args::Opt,
+    help::{print_help, print_version},
+};
+use crate::{
+    column::Column,
+    keymap::Keymap,
+    print::Format,
+    sets,
+    sort::{Sort, SortKey},
+    theme::Theme,
+    ui::Panes,
+    view::View,
+};
 
+mod args;
+mod file;
 mod help;
 
 pub struct Config {
     pub query: Option<String>,
+    /// The `brew` executable to run, from `--brew`, `BREWERI_BREW` or the `brew` key.
+    pub brew: PathBuf,
+    /// Extra environment variables for `brew` actions, from `env = NAME=VALUE` keys.
+    pub env: Vec<(String, String)>,
+    /// Print the results to stdout in this format instead of starting the TUI.
+    pub print: Option<Format>,
+    /// Which view to start in, from `--view` and the options naming a view, or the `view` key.
+    pub view: View,
+    /// Use the cached package list instead of downloading it.
+    pub offline: bool,
+    /// Names of packages to select once the package list is loaded.
+    pub select: Vec<String>,
+    /// The default keybinds with any `bind` keys applied.
+    pub keymap: Keymap,
+    /// What to show about each package in the results table.
+    pub columns: Vec<Column>,
+    /// How results are ordered, from `--sort` and `--reverse` or the `sort` and `reverse` keys.
+    pub sort: Sort,
+    /// Where the info pane goes and how big it is, from the `info` and `split` keys.
+    pub panes: Panes,
+    /// How the interface is coloured, from `--theme` or the `theme` key, or `NO_COLOR`.
+    pub theme: &'static Theme,
 }
 
 impl Config {
-    pub fn new(args: Args) -> Self {
-        let mut query: Option<String> = None;
-
-        for arg in args.skip(1) {
-            match arg.as_str() {
-                "-h" | "--help" => print_help(),
-                #[allow(clippy::option_if_let_else)]
-                _ => {
-                    if let Some(q) = query {
-                        query = Some(q + " " + &arg);
-                    } else {
-                        query = Some(arg.to_owned());
-                    }
-                }
+    /// Reads the config file and then `args`, which start with the program name.
+    pub fn new(args: impl IntoIterator<Item = String>) -> Self {
+        let parsed = args::parse(args.into_iter().skip(1)).unwrap_or_else(|err| {
+            eprintln!("breweri: {err}");
+            eprintln!("Try 'breweri --help' for more information.");
+            exit(1);
+        });
+
+        for (opt, _) in &parsed.options {
+            match opt.long {
+                "help" => print_help(),
+                "version" => print_version(),
+                _ => {}
+            }
+        }
+
+        let mut config = Self {
+            query: None,
+            brew: PathBuf::from("brew"),
+            env: Vec::new(),
+            print: None,
+            view: View::default(),
+            offline: false,
+            select: Vec::new(),
+            keymap: Keymap::default(),
+            columns: Column::DEFAULT.to_vec(),
+            sort: Sort::default(),
+            panes: Panes::default(),
+            theme: Theme::from_env(),
+        };
+
+        // Only the default config file may be missing, as a path given with --config is most
+        // likely a typo if it doesn't exist
+        let config_path = parsed
+            .options
+            .iter()
+            .rev()
+            .find(|(opt, _)| opt.long == "config")
+            .map(|(_, values)| (PathBuf::from(&values[0]), false))
+            .or_else(|| crate::dirs::config_dir().map(|dir| (dir.join("config"), true)));
+
+        if let Some((path, optional)) = config_path {
+            let pairs = file::read(&path, optional).unwrap_or_else(|err| fail(&err));
+            for (key, value) in pairs {
+                config.set(&key, value);
             }
         }
 
-        if let Err(err) = std::process::Command::new("brew").arg("--help").output() {
-            match err.kind() {
-                std::io::ErrorKind::NotFound => {
-                    eprintln!("breweri: brew not found");
+        if let Some(path) = env::var_os("BREWERI_BREW").filter(|p| !p.is_empty()) {
+            config.brew = PathBuf::from(path);
+        }
+
+        for (opt, values) in parsed.options {
+            config.apply(opt, values);
+        }
+
+        if !parsed.operands.is_empty() {
+            config.query = Some(parsed.operands.join(" "));
+        }
+
+        config
+    }
+
+    /// Applies a command line option, which takes precedence over the config file.
+    fn apply(&mut self, opt: &Opt, mut values: Vec<String>) {
+        match opt.long {
+            "help" | "version" | "config" => {}
+            "installed" => self.view = View::Installed,
+            "outdated" => self.view = View::Outdated,
+            "cask" => self.view = View::Casks,
+            "formula" => self.view = View::Formulae,
+            "select" => self.select.append(&mut values),
+            "set" => {
+                for name in values {
+                    let mut packages = sets::load(&name).unwrap_or_else(|err| fail(&err));
+                    self.select.append(&mut packages);
                 }
-                _ => {
-                    eprintln!("breweri: {err}");
+            }
+            "print" => self.print = Some(Format::Plain),
+            "json" => self.print = Some(Format::Json),
+            "offline" => self.offline = true,
+            "view" => self.view = parse_view(&values[0]),
+            "sort" => self.sort.key = parse_sort(&values[0]),
+            "reverse" => self.sort.reverse = true,
+            "theme" => self.theme = parse_theme(&values[0]),
+            "brew" => self.brew = PathBuf::from(values.swap_remove(0)),
+            long => unreachable!("--{long} is in OPTIONS but not handled"),
+        }
+    }
+
+    /// Applies a `key = value` pair from the config file.
+    fn set(&mut self, key: &str, value: String) {
+        match key {
+            "brew" => self.brew = PathBuf::from(value),
+            "env" => match value.split_once('=') {
+                Some((name, value)) => self
+                    .env
+                    .push((name.trim().to_owned(), value.trim().to_owned())),
+                None => fail(&format!("env `{value}` should look like NAME=VALUE")),
+            },
+            "offline" => self.offline = parse_bool(key, &value),
+            "view" => self.view = parse_view(&value),
+            "sort" => self.sort.key = parse_sort(&value),
+            "reverse" => self.sort.reverse = parse_bool(key, &value),
+            "info" => self.panes.set_info(&value).unwrap_or_else(|err| fail(&err)),
+            "split" => self
+                .panes
+                .set_split(&value)
+                .unwrap_or_else(|err| fail(&err)),
+            "theme" => self.theme = parse_theme(&value),
+            "bind" => self.keymap.bind(&value).unwrap_or_else(|err| fail(&err)),
+            "columns" => {
+                self.columns = Column::parse_list(&value).unwrap_or_else(|err| fail(&err));
+                if self.columns.is_empty() {
+                    fail("columns should list at least one column");
                 }
             }
-            exit(1);
+            _ => fail(&format!("unknown config key `{key}`")),
         }
+    }
+}
+
+fn parse_view(value: &str) -> View {
+    View::from_name(value).unwrap_or_else(|err| fail(&err))
+}
+
+fn parse_sort(value: &str) -> SortKey {
+    SortKey::from_name(value).unwrap_or_else(|err| fail(&err))
+}
+
+fn parse_theme(value: &str) -> &'static Theme {
+    Theme::from_name(value).unwrap_or_else(|err| fail(&err))
+}
+
+fn parse_bool(key: &str, value: &str) -> bool {
+    match value {
+        "true" | "yes" | "on" => true,
+        "false" | "no" | "off" => false,
+        _ => fail(&format!("{key} should be true or false, not `{value}`")),
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("breweri: {msg}");
+    exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(args: &[&str]) -> Config {
+        let args = ["breweri", "--config", "/dev/null"].iter().chain(args);
+        Config::new(args.map(|a| a.to_string()))
+    }
 
-        Self { query }
+    #[test]
+    fn view_options_pick_the_starting_view() {
+        assert!(config(&[]).view == View::All);
+        assert!(config(&["-i"]).view == View::Installed);
+        assert!(config(&["--outdated"]).view == View::Outdated);
+        assert!(config(&["--cask", "--view", "formulae"]).view == View::Formulae);
+        assert!(config(&["--view", "leaves", "--cask"]).view == View::Casks);
     }
 }
@@ -0,0 +1,343 @@
+/// How many values an option takes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Flag,
+    /// Exactly one value, named for the help text.
+    One(&'static str),
+    /// Every following argument up to the next option or `--`.
+    Many(&'static str),
+}
+
+pub struct Opt {
+    pub short: Option<char>,
+    pub long: &'static str,
+    pub arity: Arity,
+    pub help: &'static str,
+}
+
+/// Every option breweri accepts, in the order they are listed in `--help`.
+pub const OPTIONS: &[Opt] = &[
+    Opt {
+        short: Some('h'),
+        long: "help",
+        arity: Arity::Flag,
+        help: "Print this help and exit",
+    },
+    Opt {
+        short: Some('V'),
+        long: "version",
+        arity: Arity::Flag,
+        help: "Print the version and exit",
+    },
+    Opt {
+        short: Some('i'),
+        long: "installed",
+        arity: Arity::Flag,
+        help: "Start in the installed view, like --view installed",
+    },
+    Opt {
+        short: Some('o'),
+        long: "outdated",
+        arity: Arity::Flag,
+        help: "Start in the outdated view, like --view outdated",
+    },
+    Opt {
+        short: None,
+        long: "view",
+        arity: Arity::One("VIEW"),
+        help: "Start in VIEW: all, installed, outdated, casks, formulae or leaves",
+    },
+    Opt {
+        short: None,
+        long: "cask",
+        arity: Arity::Flag,
+        help: "Start in the casks view, like --view casks",
+    },
+    Opt {
+        short: None,
+        long: "formula",
+        arity: Arity::Flag,
+        help: "Start in the formulae view, like --view formulae",
+    },
+    Opt {
+        short: Some('s'),
+        long: "select",
+        arity: Arity::Many("NAME"),
+        help: "Select the packages named NAME on startup",
+    },
+    Opt {
+        short: None,
+        long: "set",
+        arity: Arity::Many("NAME"),
+        help: "Select the packages in the saved sets named NAME on startup",
+    },
+    Opt {
+        short: None,
+        long: "sort",
+        arity: Arity::One("MODE"),
+        help: "Sort results by MODE: relevance, name, installs-30d, installs-90d, installs-365d, \
+               recent, size, outdated, kind, installed, latest, description or catalog",
+    },
+    Opt {
+        short: Some('r'),
+        long: "reverse",
+        arity: Arity::Flag,
+        help: "Reverse the sort order",
+    },
+    Opt {
+        short: None,
+        long: "theme",
+        arity: Arity::One("NAME"),
+        help: "Use the NAME theme: dark, light, high-contrast or mono",
+    },
+    Opt {
+        short: Some('p'),
+        long: "print",
+        arity: Arity::Flag,
+        help: "Print matching packages instead of starting the TUI",
+    },
+    Opt {
+        short: None,
+        long: "json",
+        arity: Arity::Flag,
+        help: "Like --print, but as JSON",
+    },
+    Opt {
+        short: None,
+        long: "offline",
+        arity: Arity::Flag,
+        help: "Use the package list cached by the last run instead of downloading it",
+    },
+    Opt {
+        short: None,
+        long: "brew",
+        arity: Arity::One("PATH"),
+        help: "Use the brew executable at PATH",
+    },
+    Opt {
+        short: Some('c'),
+        long: "config",
+        arity: Arity::One("FILE"),
+        help: "Read the config from FILE instead of the default location",
+    },
+];
+
+pub struct Parsed {
+    /// Options in the order they were given, with their values if they take any.
+    pub options: Vec<(&'static Opt, Vec<String>)>,
+    pub operands: Vec<String>,
+}
+
+/// Parses GNU-style arguments against [`OPTIONS`].
+///
+/// Long options may be abbreviated as long as the prefix is unambiguous, short options may be
+/// bundled, and `--` ends option parsing.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Parsed, String> {
+    let mut parsed = Parsed {
+        options: Vec::new(),
+        operands: Vec::new(),
+    };
+
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            parsed.operands.extend(args);
+            break;
+        }
+
+        let (opt, inline) = if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (long, None),
+            };
+            (find_long(name)?, inline)
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            for (i, c) in shorts.char_indices() {
+                let opt = find_short(c)?;
+                if opt.arity == Arity::Flag {
+                    parsed.options.push((opt, Vec::new()));
+                    continue;
+                }
+
+                // The rest of the bundle, if any, is the value
+                let rest = &shorts[i + c.len_utf8()..];
+                let inline = (!rest.is_empty()).then(|| rest.to_owned());
+                let values = take_values(opt, inline, &mut args)?;
+                parsed.options.push((opt, values));
+                break;
+            }
+            continue;
+        } else {
+            parsed.operands.push(arg);
+            continue;
+        };
+
+        if opt.arity == Arity::Flag {
+            if inline.is_some() {
+                return Err(format!("option '--{}' doesn't allow an argument", opt.long));
+            }
+            parsed.options.push((opt, Vec::new()));
+        } else {
+            let values = take_values(opt, inline, &mut args)?;
+            parsed.options.push((opt, values));
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn take_values(
+    opt: &Opt,
+    inline: Option<String>,
+    args: &mut std::iter::Peekable<impl Iterator<Item = String>>,
+) -> Result<Vec<String>, String> {
+    let mut values = Vec::from_iter(inline);
+
+    match opt.arity {
+        Arity::Flag => {}
+        Arity::One(_) => {
+            if values.is_empty() {
+                values.extend(args.next());
+            }
+        }
+        Arity::Many(_) => {
+            while let Some(value) = args.next_if(|a| !a.starts_with('-') || a == "-") {
+                values.push(value);
+            }
+        }
+    }
+
+    if values.is_empty() {
+        return Err(format!("option '--{}' requires an argument", opt.long));
+    }
+
+    Ok(values)
+}
+
+fn find_long(name: &str) -> Result<&'static Opt, String> {
+    if let Some(opt) = OPTIONS.iter().find(|o| o.long == name) {
+        return Ok(opt);
+    }
+
+    let mut candidates = OPTIONS.iter().filter(|o| o.long.starts_with(name));
+    match (candidates.next(), candidates.next()) {
+        (Some(opt), None) if !name.is_empty() => Ok(opt),
+        (Some(_), Some(_)) => Err(format!("option '--{name}' is ambiguous")),
+        _ => Err(format!("unrecognized option '--{name}'")),
+    }
+}
+
+fn find_short(c: char) -> Result<&'static Opt, String> {
+    OPTIONS
+        .iter()
+        .find(|o| o.short == Some(c))
+        .ok_or_else(|| format!("invalid option -- '{c}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The long name and values of each option, and the operands.
+    fn parsed(args: &[&str]) -> (Vec<(&'static str, Vec<String>)>, Vec<String>) {
+        let parsed = parse(args.iter().map(|a| a.to_string())).unwrap();
+        let options = parsed
+            .options
+            .into_iter()
+            .map(|(opt, values)| (opt.long, values))
+            .collect();
+        (options, parsed.operands)
+    }
+
+    fn error(args: &[&str]) -> String {
+        match parse(args.iter().map(|a| a.to_string())) {
+            Ok(_) => panic!("{args:?} should not parse"),
+            Err(err) => err,
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let (options, operands) = parsed(&["-i", "--", "--outdated", "-r", "rust"]);
+        assert_eq!(options, [("installed", vec![])]);
+        assert_eq!(operands, strings(&["--outdated", "-r", "rust"]));
+    }
+
+    #[test]
+    fn long_options_may_be_abbreviated() {
+        let (options, _) = parsed(&["--inst", "--rev", "--sel", "fd", "--set", "rust"]);
+        assert_eq!(
+            options,
+            [
+                ("installed", vec![]),
+                ("reverse", vec![]),
+                ("select", strings(&["fd"])),
+                ("set", strings(&["rust"])),
+            ]
+        );
+
+        assert_eq!(error(&["--s", "name"]), "option '--s' is ambiguous");
+        assert_eq!(error(&["--se", "fd"]), "option '--se' is ambiguous");
+    }
+
+    #[test]
+    fn bundled_shorts_end_with_a_value() {
+        let (options, operands) = parsed(&["-pis", "foo"]);
+        assert_eq!(
+            options,
+            [
+                ("print", vec![]),
+                ("installed", vec![]),
+                ("select", strings(&["foo"])),
+            ]
+        );
+        assert!(operands.is_empty());
+
+        let (options, _) = parsed(&["-rcfile"]);
+        assert_eq!(
+            options,
+            [("reverse", vec![]), ("config", strings(&["file"]))]
+        );
+    }
+
+    #[test]
+    fn flags_reject_inline_values() {
+        assert_eq!(
+            error(&["--installed=yes"]),
+            "option '--installed' doesn't allow an argument"
+        );
+        let (options, _) = parsed(&["--theme=light", "--sort", "name"]);
+        assert_eq!(
+            options,
+            [("theme", strings(&["light"])), ("sort", strings(&["name"]))]
+        );
+    }
+
+    #[test]
+    fn many_stops_at_the_next_option() {
+        let (options, operands) = parsed(&["--select", "fd", "-", "bat", "-o", "query"]);
+        assert_eq!(
+            options,
+            [
+                ("select", strings(&["fd", "-", "bat"])),
+                ("outdated", vec![]),
+            ]
+        );
+        assert_eq!(operands, strings(&["query"]));
+
+        assert_eq!(
+            error(&["--select", "--installed"]),
+            "option '--select' requires an argument"
+        );
+    }
+
+    #[test]
+    fn unknown_options_are_named() {
+        assert_eq!(error(&["--nope"]), "unrecognized option '--nope'");
+        assert_eq!(error(&["-iz"]), "invalid option -- 'z'");
+    }
+}
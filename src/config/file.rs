@@ -0,0 +1,51 @@
+use std::{fs, io, path::Path};
+
+/// Reads `key = value` pairs from the config file at `path`.
+///
+/// Blank lines and lines starting with `#` are ignored. A missing file is treated as empty if
+/// `optional` is set, as the default config file need not exist, and is an error otherwise.
+pub fn read(path: &Path, optional: bool) -> Result<Vec<(String, String)>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && optional => return Ok(Vec::new()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(format!("{}: No such file or directory", path.display()))
+        }
+        Err(err) => return Err(format!("{}: {err}", path.display())),
+    };
+
+    let mut out = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!(
+                "{}:{}: expected `key = value`",
+                path.display(),
+                i + 1
+            ));
+        };
+
+        out.push((key.trim().to_owned(), value.trim().to_owned()));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_optional_files_may_be_missing() {
+        let path = Path::new("/nonexistent/breweri.conf");
+        assert_eq!(read(path, true), Ok(Vec::new()));
+        assert_eq!(
+            read(path, false),
+            Err("/nonexistent/breweri.conf: No such file or directory".to_owned())
+        );
+    }
+}
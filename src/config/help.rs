@@ -1,55 +1,51 @@
 use std::process::exit;
 
-pub fn print_help() {
+use super::args::{Arity, OPTIONS};
+use crate::{keymap::Keymap, mode::Mode};
+
+pub fn print_help() -> ! {
+    let mut options = String::new();
+    for opt in OPTIONS {
+        options.push_str("   ");
+        if let Some(short) = opt.short {
+            options.push_str(&format!("-{short}, "));
+        }
+        options.push_str("--");
+        options.push_str(opt.long);
+        match opt.arity {
+            Arity::Flag => {}
+            Arity::One(name) => options.push_str(&format!(" {name}")),
+            Arity::Many(name) => options.push_str(&format!(" {name}...")),
+        }
+        options.push_str("\n       ");
+        options.push_str(opt.help);
+        options.push('\n');
+    }
+
+    let keymap = Keymap::default();
+    let mut keybinds = String::from("Keybinds:");
+    for mode in Mode::ALL {
+        let mut name = mode.name().to_owned();
+        name[..1].make_ascii_uppercase();
+        keybinds.push_str(&format!("\n   {name}:"));
+        for (keys, description) in keymap.describe(mode) {
+            keybinds.push_str(&format!("\n       {keys}\n           {description}"));
+        }
+    }
+
     println!(
-        r#"Usage: breweri [OPTION]... QUERY
+        r#"Usage: breweri [OPTION]... [--] QUERY
 Search for QUERY in Homebrew repositories,
 Example:
    breweri rustup
 
 Options:
-   -h
-       Print this help and exit
-Keybinds:
-    Both:
-       <Escape>
-           Switch Modes
-       <C-c>
-           Exit parui
-   Insert:
-       <Return>
-           Search for query
-       <C-w>
-           Remove previous word
-   Select:
-       i, /
-           Enter insert mode
-       <Return>
-           Install selected packages
-       <C-j>, <C-Down>
-           Move info one row down
-       <C-k>, <C-Up>
-           Move info one row up
-       h, <Left>, <PgUp>
-           Move one page back
-       j, <Down>
-           Move one row down
-       k, <Up>
-           Move one row up
-       l, <Right>, <PgDn>
-           Move one page forwards
-       g, <Home>
-           Go to start
-       G, <End>
-           Go to end
-       <Space>
-           Select/deselect package
-       c
-           Clear selections
-       <S-R>
-           Remove selected packages
-       q
-           Exit breweri"#
+{options}{keybinds}"#
     );
     exit(0);
 }
+
+pub fn print_version() -> ! {
+    println!("breweri {}", env!("CARGO_PKG_VERSION"));
+    exit(0);
+}
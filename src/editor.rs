@@ -0,0 +1,279 @@
+use std::cell::Cell;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A single line of editable text with a cursor that always sits on a grapheme boundary.
+#[derive(Default)]
+pub struct LineEditor {
+    text: String,
+    /// Byte offset of the cursor into `text`.
+    cursor: usize,
+    /// The last text removed by a kill command, for yanking back.
+    kill: String,
+    /// Display column of the first visible cell, kept between frames so the view only scrolls
+    /// when the cursor would leave it.
+    scroll: Cell<usize>,
+}
+
+impl LineEditor {
+    pub fn new(text: String) -> Self {
+        Self {
+            cursor: text.len(),
+            text,
+            ..Self::default()
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, moving the cursor to the end.
+    pub fn set_text(&mut self, text: String) {
+        self.cursor = text.len();
+        self.text = text;
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or_default()
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map(|g| self.cursor + g.len())
+            .unwrap_or(self.text.len())
+    }
+
+    /// The start of the word before the cursor, skipping any separators in between.
+    fn word_start(&self) -> usize {
+        let mut start = self.cursor;
+        let mut seen_word = false;
+        for (i, g) in self.text[..self.cursor].grapheme_indices(true).rev() {
+            if is_separator(g) {
+                if seen_word {
+                    break;
+                }
+            } else {
+                seen_word = true;
+            }
+            start = i;
+        }
+        start
+    }
+
+    /// The end of the word after the cursor, skipping any separators in between.
+    fn word_end(&self) -> usize {
+        let mut end = self.cursor;
+        let mut seen_word = false;
+        for g in self.text[self.cursor..].graphemes(true) {
+            if is_separator(g) {
+                if seen_word {
+                    break;
+                }
+            } else {
+                seen_word = true;
+            }
+            end += g.len();
+        }
+        end
+    }
+
+    /// Moves one grapheme left, wrapping around to the end.
+    pub fn left(&mut self) {
+        self.cursor = if self.cursor == 0 {
+            self.text.len()
+        } else {
+            self.prev_boundary()
+        };
+    }
+
+    /// Moves one grapheme right, wrapping around to the start.
+    pub fn right(&mut self) {
+        self.cursor = if self.cursor == self.text.len() {
+            0
+        } else {
+            self.next_boundary()
+        };
+    }
+
+    pub fn word_left(&mut self) {
+        self.cursor = self.word_start();
+    }
+
+    pub fn word_right(&mut self) {
+        self.cursor = self.word_end();
+    }
+
+    pub fn start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.len();
+    }
+
+    pub fn delete_back(&mut self) {
+        let start = self.prev_boundary();
+        self.text.drain(start..self.cursor);
+        self.cursor = start;
+    }
+
+    pub fn delete_forward(&mut self) {
+        let end = self.next_boundary();
+        self.text.drain(self.cursor..end);
+    }
+
+    /// Removes the word before the cursor, saving it for [`LineEditor::yank`].
+    pub fn kill_word_back(&mut self) {
+        let start = self.word_start();
+        self.kill_range(start, self.cursor);
+    }
+
+    pub fn kill_to_start(&mut self) {
+        self.kill_range(0, self.cursor);
+    }
+
+    pub fn kill_to_end(&mut self) {
+        self.kill_range(self.cursor, self.text.len());
+    }
+
+    fn kill_range(&mut self, start: usize, end: usize) {
+        if start == end {
+            return;
+        }
+
+        self.kill = self.text.drain(start..end).collect();
+        self.cursor = start;
+    }
+
+    /// Inserts the last killed text at the cursor.
+    pub fn yank(&mut self) {
+        self.text.insert_str(self.cursor, &self.kill);
+        self.cursor += self.kill.len();
+    }
+
+    /// Returns the part of the text that fits in `width` cells and the cursor's column in it,
+    /// scrolling just enough to keep the cursor visible.
+    pub fn view(&self, width: usize) -> (String, u16) {
+        // Leave a cell for the cursor to sit on past the end of the text
+        let width = width.saturating_sub(1).max(1);
+        let cursor_col = self.text[..self.cursor].width();
+
+        let mut scroll = self.scroll.get().min(cursor_col);
+        if cursor_col >= scroll + width {
+            scroll = cursor_col + 1 - width;
+        }
+        self.scroll.set(scroll);
+
+        let mut col = 0;
+        let mut visible = String::new();
+        for g in self.text.graphemes(true) {
+            let w = g.width();
+            if col >= scroll && col + w <= scroll + width {
+                visible.push_str(g);
+            } else if col < scroll && col + w > scroll {
+                // Pad out the half of a wide character that was scrolled off
+                visible.extend(std::iter::repeat(' ').take(col + w - scroll));
+            } else if col >= scroll + width {
+                break;
+            }
+            col += w;
+        }
+
+        (visible, (cursor_col - scroll) as u16)
+    }
+}
+
+fn is_separator(grapheme: &str) -> bool {
+    matches!(grapheme, " " | "-" | "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An e followed by a combining acute accent, which together make one grapheme.
+    const E_ACUTE: &str = "e\u{301}";
+
+    #[test]
+    fn delete_back_removes_whole_graphemes() {
+        let mut editor = LineEditor::new(format!("caf{E_ACUTE}"));
+        editor.delete_back();
+        assert_eq!(editor.text(), "caf");
+
+        let mut editor = LineEditor::new("a日本".to_owned());
+        editor.delete_back();
+        assert_eq!(editor.text(), "a日");
+        editor.delete_back();
+        editor.delete_back();
+        editor.delete_back();
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn left_and_right_step_over_graphemes() {
+        let mut editor = LineEditor::new(format!("{E_ACUTE}日x"));
+        editor.left();
+        assert_eq!(editor.cursor, E_ACUTE.len() + "日".len());
+        editor.left();
+        assert_eq!(editor.cursor, E_ACUTE.len());
+        editor.left();
+        assert_eq!(editor.cursor, 0);
+        editor.left();
+        assert_eq!(editor.cursor, editor.text().len());
+
+        editor.right();
+        assert_eq!(editor.cursor, 0);
+        editor.right();
+        assert_eq!(editor.cursor, E_ACUTE.len());
+        editor.insert('!');
+        assert_eq!(editor.text(), format!("{E_ACUTE}!日x"));
+    }
+
+    #[test]
+    fn kill_word_back_then_yank() {
+        let mut editor = LineEditor::new(format!("brew caf{E_ACUTE} 日本"));
+        editor.kill_word_back();
+        assert_eq!(editor.text(), format!("brew caf{E_ACUTE} "));
+        editor.kill_word_back();
+        assert_eq!(editor.text(), "brew ");
+
+        editor.start();
+        editor.yank();
+        assert_eq!(editor.text(), format!("caf{E_ACUTE} brew "));
+        assert_eq!(editor.cursor, format!("caf{E_ACUTE} ").len());
+    }
+
+    #[test]
+    fn view_scrolls_across_wide_characters() {
+        // Three cells are left for the text in a view four wide
+        let mut editor = LineEditor::new("ab日本".to_owned());
+        assert_eq!(editor.view(4), ("本".to_owned(), 2));
+
+        // The half of 日 scrolled off the left is padded out
+        let wider = LineEditor::new("ab日本".to_owned());
+        assert_eq!(wider.view(5), (" 本".to_owned(), 3));
+
+        // The view only scrolls back once the cursor would leave it
+        editor.left();
+        assert_eq!(editor.view(4), ("本".to_owned(), 0));
+        editor.left();
+        assert_eq!(editor.view(4), ("日".to_owned(), 0));
+        editor.start();
+        assert_eq!(editor.view(4), ("ab".to_owned(), 0));
+        editor.end();
+        assert_eq!(editor.view(4), ("本".to_owned(), 2));
+    }
+}
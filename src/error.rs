@@ -0,0 +1,70 @@
+use std::fmt;
+
+/// What went wrong, which decides the hint shown with an [`Error`] and whether retrying may help.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A program breweri runs, such as `curl`, isn't installed.
+    Missing(&'static str),
+    /// Downloading from the Homebrew API failed.
+    Network,
+    /// `jq` couldn't make sense of what was downloaded.
+    Jq,
+    /// `--offline` was given but nothing has been cached yet.
+    NoCache,
+    /// The Cellar or Caskroom couldn't be read.
+    Cellar,
+    /// `brew` couldn't tell where a package's Ruby file is.
+    Source,
+}
+
+/// A failure, with the details needed to tell what happened.
+#[derive(Clone)]
+pub struct Error {
+    pub kind: ErrorKind,
+    /// What the failing program or call had to say, which may be empty.
+    pub detail: String,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            detail: detail.into(),
+        }
+    }
+
+    /// What the user can do about it.
+    pub fn hint(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::Missing(_) => "install it or use --offline",
+            ErrorKind::Network => "check your connection or use --offline",
+            ErrorKind::Jq => "the Homebrew API may have changed, try --offline",
+            ErrorKind::NoCache => "run breweri once without --offline",
+            ErrorKind::Cellar => "check the permissions of your Homebrew prefix",
+            ErrorKind::Source => "its tap may need to be cloned with `brew tap --force`",
+        }
+    }
+
+    /// Whether trying again without restarting breweri might work.
+    pub fn is_recoverable(&self) -> bool {
+        self.kind != ErrorKind::NoCache
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::Missing(program) => write!(f, "{program} not found")?,
+            ErrorKind::Network => f.write_str("couldn't download the package list")?,
+            ErrorKind::Jq => f.write_str("couldn't parse the package list")?,
+            ErrorKind::NoCache => f.write_str("no cached package list")?,
+            ErrorKind::Cellar => f.write_str("couldn't read installed packages")?,
+            ErrorKind::Source => f.write_str("couldn't find the package's source")?,
+        }
+
+        if !self.detail.is_empty() {
+            write!(f, " ({})", self.detail)?;
+        }
+        Ok(())
+    }
+}
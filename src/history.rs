@@ -0,0 +1,124 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::dirs;
+
+/// How many queries are kept, older ones are dropped the next time the file is loaded.
+const MAX_ENTRIES: usize = 1000;
+
+/// Submitted queries, oldest first, persisted to `$XDG_STATE_HOME/breweri/history`.
+#[derive(Default)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+    /// The entry being shown while walking the history, `None` when editing a fresh query.
+    pos: Option<usize>,
+    /// The query that was being typed before walking the history started.
+    draft: String,
+}
+
+impl History {
+    /// Loads the history file. Failing to read it just means starting with no history.
+    pub fn load() -> Self {
+        let path = dirs::state_dir().map(|dir| dir.join("history"));
+        let contents = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+
+        let mut history = Self {
+            path,
+            ..Self::default()
+        };
+        let lines = contents.lines().filter(|l| !l.is_empty()).count();
+        for line in contents.lines().filter(|l| !l.is_empty()) {
+            history.add(line.to_owned());
+        }
+
+        let excess = history.entries.len().saturating_sub(MAX_ENTRIES);
+        history.entries.drain(..excess);
+        if lines > history.entries.len() {
+            history.rewrite();
+        }
+
+        history
+    }
+
+    /// Moves `query` to the end of the history, removing any earlier copy of it.
+    fn add(&mut self, query: String) {
+        self.entries.retain(|e| *e != query);
+        self.entries.push(query);
+    }
+
+    /// Records a submitted query and stops walking the history.
+    pub fn push(&mut self, query: &str) {
+        self.pos = None;
+        if query.is_empty() || self.entries.last().is_some_and(|e| e == query) {
+            return;
+        }
+
+        self.add(query.to_owned());
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        if path
+            .parent()
+            .is_some_and(|dir| fs::create_dir_all(dir).is_ok())
+        {
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "{query}");
+            }
+        }
+    }
+
+    /// Replaces the file with the deduplicated, truncated entries.
+    fn rewrite(&self) {
+        if let Some(path) = &self.path {
+            let mut contents = self.entries.join("\n");
+            contents.push('\n');
+            let _ = fs::write(path, contents);
+        }
+    }
+
+    /// Steps to the previous query, saving `current` so [`History::newer`] can return to it.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let pos = match self.pos {
+            Some(0) => return None,
+            Some(pos) => pos - 1,
+            None => {
+                self.draft = current.to_owned();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+
+        self.pos = Some(pos);
+        Some(&self.entries[pos])
+    }
+
+    /// Steps to the next query, or back to the one being typed after the newest.
+    pub fn newer(&mut self) -> Option<&str> {
+        let pos = self.pos? + 1;
+        if pos == self.entries.len() {
+            self.pos = None;
+            return Some(&self.draft);
+        }
+
+        self.pos = Some(pos);
+        Some(&self.entries[pos])
+    }
+
+    /// The newest entry containing `pattern`, searching from just before `before` if given.
+    pub fn find(&self, pattern: &str, before: Option<usize>) -> Option<usize> {
+        self.entries[..before.unwrap_or(self.entries.len())]
+            .iter()
+            .rposition(|e| e.contains(pattern))
+    }
+
+    pub fn get(&self, idx: usize) -> &str {
+        &self.entries[idx]
+    }
+}
@@ -0,0 +1,115 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use nohash_hasher::{IntMap, IntSet};
+
+use crate::{catalog::Catalog, sort::natural_cmp};
+
+/// How much disk space each installed package takes up, by index into the [`Catalog`].
+pub type Sizes = IntMap<usize, u64>;
+
+/// Installed formulae that no other installed formula depends on, by index into the [`Catalog`].
+pub type Leaves = IntSet<usize>;
+
+/// One installed version of a package.
+pub struct Keg {
+    pub version: String,
+    /// When the version was installed, if the backend knows.
+    pub installed_at: Option<SystemTime>,
+}
+
+impl Keg {
+    pub fn new(version: String) -> Self {
+        Self {
+            version,
+            installed_at: None,
+        }
+    }
+}
+
+/// The installed packages and their versions, by index into the [`Catalog`].
+#[derive(Default)]
+pub struct Installed {
+    versions: IntMap<usize, String>,
+    installed_at: IntMap<usize, SystemTime>,
+}
+
+impl Installed {
+    /// Matches installed packages to the catalog.
+    ///
+    /// `found` maps package names to every version found on disk. If several are installed, the
+    /// one matching the catalog is preferred so that only stale installs count as outdated.
+    pub fn new(catalog: &Catalog, mut found: HashMap<String, Vec<Keg>>) -> Self {
+        let mut versions = IntMap::default();
+        let mut installed_at = IntMap::default();
+
+        for (i, name) in catalog.iter().enumerate() {
+            let Some(mut candidates) = found.remove(name) else {
+                continue;
+            };
+
+            let latest = catalog.version(i);
+            let keg = match candidates.iter().position(|keg| keg.version == latest) {
+                Some(pos) => candidates.swap_remove(pos),
+                None => {
+                    candidates.sort_unstable_by(|a, b| natural_cmp(&a.version, &b.version));
+                    candidates.pop().unwrap_or_else(|| Keg::new(String::new()))
+                }
+            };
+
+            if let Some(time) = keg.installed_at {
+                installed_at.insert(i, time);
+            }
+            versions.insert(i, keg.version);
+        }
+
+        Self {
+            versions,
+            installed_at,
+        }
+    }
+
+    pub fn contains(&self, index: &usize) -> bool {
+        self.versions.contains_key(index)
+    }
+
+    /// The installed version of the package at `index`, which may be empty if it is unknown.
+    pub fn version(&self, index: usize) -> Option<&str> {
+        self.versions.get(&index).map(String::as_str)
+    }
+
+    /// When the installed version of the package at `index` was installed, if known.
+    pub fn installed_at(&self, index: usize) -> Option<SystemTime> {
+        self.installed_at.get(&index).copied()
+    }
+
+    /// The indices of every installed package.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.versions.keys().copied()
+    }
+
+    /// Whether the package at `index` is installed at a version other than the latest.
+    ///
+    /// Packages with unknown versions, such as casks versioned `latest`, are never outdated.
+    pub fn is_outdated(&self, catalog: &Catalog, index: usize) -> bool {
+        let latest = catalog.version(index);
+        match self.version(index) {
+            Some(version) => {
+                !version.is_empty() && !latest.is_empty() && latest != "latest" && version != latest
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_keg_wins_without_a_match() {
+        let catalog = Catalog::parse(b"node\t11.0.0\tJavaScript runtime\n", b"");
+        let kegs = ["9.0.0", "10.1.0"].map(|v| Keg::new(v.to_owned())).into();
+        let installed = Installed::new(&catalog, HashMap::from([("node".to_owned(), kegs)]));
+        assert_eq!(installed.version(0), Some("10.1.0"));
+    }
+}
@@ -1,244 +1,453 @@
-use std::{borrow::Cow, collections::HashSet, process::Stdio, sync::Arc, time::Duration};
+use std::{ops::Range, time::Duration};
 
-use arc_swap::ArcSwap;
-use compact_strings::CompactStrings;
 use nohash_hasher::IntSet;
-use tokio::{join, process::Command, time::sleep};
+use tokio::time::sleep;
 use tui::{
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint},
+    style::{Modifier, Style},
     text::{Line, Span},
+    widgets::{Cell, Row, Table},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    backend::Backend,
+    catalog::{Catalog, Installs},
+    column::Column,
+    installed::Installed,
+    shown::Shown,
+    sort::{Context, Sort},
+    theme::Theme,
+    view::View,
 };
 
-use crate::shown::Shown;
-
-pub async fn list() -> &'static mut CompactStrings {
-    let formulae = Command::new("curl")
-        .arg("-s")
-        .arg("https://formulae.brew.sh/api/formula.json")
-        .stdout(Stdio::piped())
-        .spawn()
-        .unwrap();
-    let formulae_jq = Command::new("jq")
-        .arg("-r")
-        .arg(".[]|.name")
-        .stdin(Stdio::from(
-            formulae.stdout.unwrap().into_owned_fd().unwrap(),
-        ))
-        .stdout(Stdio::piped())
-        .spawn();
-    let casks = Command::new("curl")
-        .arg("-s")
-        .arg("https://formulae.brew.sh/api/cask.json")
-        .stdout(Stdio::piped())
-        .spawn()
-        .unwrap();
-    let casks_jq = Command::new("jq")
-        .arg("-r")
-        .arg(".[]|.full_token")
-        .stdin(Stdio::from(casks.stdout.unwrap().into_owned_fd().unwrap()))
-        .stdout(Stdio::piped())
-        .spawn();
-
-    let out = Box::leak(Box::new(CompactStrings::with_capacity(16 * 16384, 16384)));
-
-    let Ok(formulae_jq) = formulae_jq else {
-        return out;
-    };
+pub fn search(query: &str, ctx: &Context, view: View, sort: Sort) -> Shown {
+    let packages = ctx.catalog;
 
-    let Ok(casks_jq) = casks_jq else {
-        return out;
+    if query.is_empty() && view == View::All && sort.is_catalog_order(query) {
+        Shown::All
+    } else {
+        let mut found = packages
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| find_hit(packages, *i, query).is_some() && view.matches(ctx, *i))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        sort.apply(&mut found, ctx);
+        Shown::Few(found)
+    }
+}
+
+/// Reorders already shown packages, such as after the sort mode changed.
+pub fn sort_shown(shown: &Shown, ctx: &Context, sort: Sort) -> Shown {
+    let mut indices = match shown.get_vec() {
+        Some(indices) => indices.clone(),
+        None if sort.is_catalog_order(ctx.query) => return Shown::All,
+        None => (0..ctx.catalog.len()).collect(),
     };
+    sort.apply(&mut indices, ctx);
+    Shown::Few(indices)
+}
 
-    let (formulae, casks) = join!(formulae_jq.wait_with_output(), casks_jq.wait_with_output());
+/// Where a query was found in a package, as a byte range.
+pub enum Hit {
+    Name(Range<usize>),
+    /// Only when the name didn't match, as the description is matched ignoring ASCII case.
+    Description(Range<usize>),
+}
 
-    let Ok(formulae) = formulae else {
-        return out;
-    };
+/// Finds `query` in the name of the package at `index`, or failing that its description.
+pub fn find_hit(packages: &Catalog, index: usize, query: &str) -> Option<Hit> {
+    if let Some(pos) = packages[index].find(query) {
+        return Some(Hit::Name(pos..pos + query.len()));
+    }
 
-    let Ok(casks) = casks else {
-        return out;
+    let description = packages.description(index);
+    let pos = description
+        .to_ascii_lowercase()
+        .find(&query.to_ascii_lowercase())?;
+    Some(Hit::Description(pos..pos + query.len()))
+}
+
+/// A cell's text, style and matched range, before it is cut down to size.
+type CellText = (String, Style, Option<Range<usize>>);
+
+/// The rows of results that fit on screen, and which result each shows.
+pub struct Results<'a> {
+    pub table: Table<'a>,
+    /// The position in the results and height of each row drawn, from the top.
+    pub rows: Vec<(usize, u16)>,
+}
+
+/// Builds the table of packages on the page starting at `skip`, fitted into `width` columns and
+/// `height` rows.
+///
+/// Packages found by their description get a second line showing where. If that makes the page
+/// too tall, rows are dropped from the top or bottom so that `current` stays visible.
+#[allow(clippy::too_many_arguments)]
+pub fn format_results<'a>(
+    packages: &'a Catalog,
+    shown: &Shown,
+    current: usize,
+    selected: &IntSet<usize>,
+    per_page: usize,
+    skip: usize,
+    installed: &'a Installed,
+    installs: Option<&Installs>,
+    columns: &[Column],
+    sort: Sort,
+    query: &str,
+    theme: &Theme,
+    width: u16,
+    height: u16,
+) -> Results<'a> {
+    let page = match shown.get_vec() {
+        Some(shown) => shown.iter().skip(skip).take(per_page).copied().collect(),
+        None => (skip..packages.len().min(skip + per_page)).collect::<Vec<_>>(),
     };
+    let shown_len = shown.len().unwrap_or(packages.len());
+    let period = sort.key.period();
 
-    let mut buf = Vec::with_capacity(128);
+    let titles = columns
+        .iter()
+        .map(|&column| {
+            let title = match column {
+                Column::Installs => format!("{} {}", column.title(), period.as_str()),
+                _ => column.title().to_owned(),
+            };
+            if sort != Sort::default() && column.sort_key(sort) == sort.key {
+                format!("{title} {}", sort.arrow())
+            } else {
+                title
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let hits = page
+        .iter()
+        .map(|&i| find_hit(packages, i, query).filter(|_| !query.is_empty()))
+        .collect::<Vec<_>>();
 
-    for byte in formulae.stdout.into_iter().chain(casks.stdout.into_iter()) {
-        if byte != b'\n' {
-            buf.push(byte);
-            continue;
-        }
+    let cells = page
+        .iter()
+        .zip(&hits)
+        .enumerate()
+        .map(|(row, (&i, hit))| {
+            let outdated = installed.is_outdated(packages, i);
+            columns
+                .iter()
+                .map(|column| match column {
+                    Column::Index => ((skip + row + 1).to_string(), theme.dim, None),
+                    Column::Name => (
+                        packages[i].to_owned(),
+                        match (installed.contains(&i), current == skip + row) {
+                            (true, true) => theme.installed_current,
+                            (true, false) => theme.installed,
+                            (false, true) => theme.uninstalled_current,
+                            (false, false) => theme.uninstalled,
+                        },
+                        match hit {
+                            Some(Hit::Name(range)) => Some(range.clone()),
+                            _ => None,
+                        },
+                    ),
+                    Column::Kind => (packages.kind(i).as_str().to_owned(), Style::default(), None),
+                    Column::Installed => (
+                        installed.version(i).unwrap_or_default().to_owned(),
+                        Style::default(),
+                        None,
+                    ),
+                    Column::Latest => (
+                        packages.version(i).to_owned(),
+                        if outdated {
+                            theme.outdated
+                        } else {
+                            Style::default()
+                        },
+                        None,
+                    ),
+                    Column::Installs => (
+                        installs.map_or_else(String::new, |installs| {
+                            group_digits(installs.get(period, i).into())
+                        }),
+                        Style::default(),
+                        None,
+                    ),
+                    Column::Description => (
+                        packages.description(i).to_owned(),
+                        theme.dim,
+                        match hit {
+                            Some(Hit::Description(range)) => Some(range.clone()),
+                            _ => None,
+                        },
+                    ),
+                })
+                .collect::<Vec<CellText>>()
+        })
+        .collect::<Vec<_>>();
+
+    let widths = fit_widths(columns, &titles, &cells, shown_len, width);
+
+    // The second line goes under the description, or the name if that isn't shown
+    let fragment_column = columns
+        .iter()
+        .position(|c| *c == Column::Description)
+        .filter(|&c| widths[c] > 0)
+        .or_else(|| columns.iter().position(|c| *c == Column::Name));
+    let fragments = page
+        .iter()
+        .zip(&hits)
+        .map(|(&i, hit)| {
+            let (Some(Hit::Description(range)), Some(c)) = (hit, fragment_column) else {
+                return None;
+            };
+            let description = packages.description(i);
+            // Only needed if the match was cut off in the description column
+            let (kept, _) = fit(description, widths[c]);
+            if columns[c] == Column::Description && range.end <= kept {
+                return None;
+            }
+            Some(fragment(description, range.clone(), widths[c]))
+        })
+        .collect::<Vec<_>>();
+
+    // Keep the current row on screen, then fill the rest of the height
+    let heights = fragments
+        .iter()
+        .map(|f| if f.is_some() { 2 } else { 1 })
+        .collect::<Vec<u16>>();
+    let budget = height.saturating_sub(1);
+    let current_row = current
+        .saturating_sub(skip)
+        .min(page.len().saturating_sub(1));
+    let mut first = 0;
+    while first < current_row && heights[first..=current_row].iter().sum::<u16>() > budget {
+        first += 1;
+    }
+    let mut used = 0;
+    let visible = heights[first..]
+        .iter()
+        .take_while(|&&h| {
+            used += h;
+            used <= budget
+        })
+        .count();
+
+    let header = Row::new(
+        titles
+            .into_iter()
+            .zip(&widths)
+            .map(|(title, &width)| Cell::from(truncate(title, width)).style(theme.header)),
+    );
 
-        if let Ok(s) = std::str::from_utf8(&buf) {
-            out.push(s);
-        }
+    let mut layout = Vec::with_capacity(visible);
+    let rows = cells
+        .into_iter()
+        .zip(&page)
+        .zip(fragments)
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(row, ((cells, &i), fragment))| {
+            layout.push((skip + row, heights[row]));
+            let cells = cells.into_iter().zip(columns).zip(&widths).enumerate().map(
+                |(c, (((text, style, hit), column), &width))| {
+                    let line = if *column == Column::Name && selected.contains(&i) {
+                        // Keep room for the marker
+                        let mut line = highlight(text, style, hit, width.saturating_sub(1), theme);
+                        line.spans.push(Span::styled("!", theme.marker));
+                        line
+                    } else if *column == Column::Installs {
+                        highlight(text, style, hit, width, theme).alignment(Alignment::Right)
+                    } else {
+                        highlight(text, style, hit, width, theme)
+                    };
+
+                    match &fragment {
+                        Some((text, hit)) if fragment_column == Some(c) => {
+                            let second =
+                                highlight(text.clone(), theme.dim, Some(hit.clone()), width, theme);
+                            Cell::from(vec![line, second])
+                        }
+                        _ => Cell::from(line),
+                    }
+                },
+            );
+            Row::new(cells.collect::<Vec<_>>()).height(heights[row])
+        })
+        .collect::<Vec<_>>();
+
+    let constraints = widths
+        .iter()
+        .copied()
+        .map(Constraint::Length)
+        .collect::<Vec<_>>();
+    Results {
+        table: Table::new(rows, constraints).header(header),
+        rows: layout,
+    }
+}
 
-        buf.clear();
+/// Sizes each column to its contents, squeezing the description and then dropping columns from
+/// the right until they all fit in `width`.
+fn fit_widths(
+    columns: &[Column],
+    titles: &[String],
+    cells: &[Vec<CellText>],
+    shown_len: usize,
+    width: u16,
+) -> Vec<u16> {
+    let mut widths = columns
+        .iter()
+        .enumerate()
+        .map(|(c, column)| {
+            let content = cells
+                .iter()
+                .map(|row| row[c].0.width())
+                .max()
+                .unwrap_or_default();
+            let content = match column {
+                Column::Index => shown_len.to_string().len(),
+                // Room for the selection marker
+                Column::Name => content + 1,
+                Column::Installed | Column::Latest => content.min(16),
+                _ => content,
+            };
+            content.max(titles[c].width()) as u16
+        })
+        .collect::<Vec<_>>();
+
+    // Columns are separated by a space
+    let needed = widths.iter().sum::<u16>() + widths.len().saturating_sub(1) as u16;
+    let mut overflow = needed.saturating_sub(width);
+    if let Some(c) = columns.iter().position(|c| *c == Column::Description) {
+        let min = titles[c].width() as u16;
+        let shrink = overflow.min(widths[c].saturating_sub(min));
+        widths[c] -= shrink;
+        overflow -= shrink;
     }
 
-    out.shrink_to_fit();
-    out.shrink_meta_to_fit();
+    if overflow > 0 {
+        let mut remaining = width;
+        for w in &mut widths {
+            *w = (*w).min(remaining);
+            remaining = remaining.saturating_sub(*w + 1);
+        }
+    }
 
-    out
+    widths
 }
 
-pub fn search(query: &str, packages: &CompactStrings) -> Shown {
-    if query.is_empty() {
-        Shown::All
-    } else {
-        Shown::Few(
-            packages
-                .iter()
-                .enumerate()
-                .filter(|(_, package)| package.contains(query))
-                .map(|(i, _)| i)
-                .collect(),
-        )
+/// How many bytes of `text` fit in `width` columns, and whether an ellipsis is needed after
+/// them because the rest was cut.
+fn fit(text: &str, width: u16) -> (usize, bool) {
+    let width = width as usize;
+    if text.width() <= width {
+        return (text.len(), false);
     }
+
+    let mut kept = 0;
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or_default();
+        if used + w + 1 > width {
+            break;
+        }
+        kept += c.len_utf8();
+        used += w;
+    }
+    (kept, width > 0)
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn format_results<'line>(
-    packages: &'static CompactStrings,
-    shown: Arc<ArcSwap<Shown>>,
-    current: usize,
-    selected: &IntSet<usize>,
-    height: usize,
-    pad_to: usize,
-    skip: usize,
-    installed: &IntSet<usize>,
-) -> Vec<Line<'line>> {
-    use crate::{cows, style};
+/// Cuts `text` down to `width` columns, ending it with an ellipsis if anything was cut.
+fn truncate(mut text: String, width: u16) -> String {
+    let (kept, cut) = fit(&text, width);
+    text.truncate(kept);
+    if cut {
+        text.push('…');
+    }
+    text
+}
 
-    const INDEX_STYLE: Style = style!(Color::Gray);
-    const INSTALLED_STYLE: Style = style! {
-        fg: Color::Green,
-        mod: Modifier::BOLD,
-    };
-    const INSTALLED_SELECTED_STYLE: Style = style! {
-        fg: Color::Yellow,
-        bg: Color::Red,
-        mod: Modifier::BOLD,
-    };
-    const UNINSTALLED_STYLE: Style = style! {
-        fg: Color::LightBlue,
-        mod: Modifier::BOLD,
-    };
-    const UNINSTALLED_SELECTED_STYLE: Style = style! {
-        fg: Color::Blue,
-        bg: Color::Red,
-        mod: Modifier::BOLD,
-    };
-    const DEFAULT_STYLE: Style = style!();
-
-    const PADDINGS: [Cow<'static, str>; 16] = cows!(
-        " ",
-        "  ",
-        "   ",
-        "    ",
-        "     ",
-        "      ",
-        "       ",
-        "        ",
-        "         ",
-        "          ",
-        "           ",
-        "            ",
-        "             ",
-        "              ",
-        "               ",
-        "                "
-    );
+/// Cuts `text` down to `width` columns, emphasising the part in `hit` that is still shown.
+fn highlight(
+    text: String,
+    style: Style,
+    hit: Option<Range<usize>>,
+    width: u16,
+    theme: &Theme,
+) -> Line<'static> {
+    let (kept, cut) = fit(&text, width);
+    let mut spans = Vec::new();
+    match hit.filter(|hit| hit.start < kept && !hit.is_empty()) {
+        Some(hit) => {
+            let end = hit.end.min(kept);
+            spans.push(Span::styled(text[..hit.start].to_owned(), style));
+            spans.push(Span::styled(
+                text[hit.start..end].to_owned(),
+                style.patch(theme.query_match),
+            ));
+            spans.push(Span::styled(text[end..kept].to_owned(), style));
+        }
+        None => spans.push(Span::styled(text[..kept].to_owned(), style)),
+    }
+    if cut {
+        spans.push(Span::styled("…", style));
+    }
+    Line::from(spans)
+}
 
-    const SELECTED: Span = Span {
-        content: Cow::Borrowed("!"),
-        style: style! { fg: Color::Yellow, mod: Modifier::BOLD, },
-    };
+/// The part of `description` around `hit` that fits in `width` columns, with `hit` moved to
+/// match.
+fn fragment(description: &str, hit: Range<usize>, width: u16) -> (String, Range<usize>) {
+    // Show some of what comes before the match, but not so much that the match is cut off
+    let context = (width as usize / 3).min(hit.start);
+    let mut start = hit.start - context;
+    while !description.is_char_boundary(start) {
+        start -= 1;
+    }
+    if start == 0 {
+        return (description.to_owned(), hit);
+    }
 
-    match (*shown).load().get_vec() {
-        Some(shown) => shown
-            .iter()
-            .skip(skip)
-            .take(height - 5)
-            .copied()
-            .enumerate()
-            .map(|(i, package_idx)| {
-                let real_index = shown[skip + i];
-                let index = i + skip + 1;
-
-                let index_span = Span::styled(index.to_string(), INDEX_STYLE);
-                let padding_span = Span {
-                    content: PADDINGS[pad_to - index.ilog10() as usize].clone(),
-                    style: DEFAULT_STYLE,
-                };
-                let line_span = Span::styled(
-                    &packages[package_idx],
-                    match (installed.contains(&real_index), current == index - 1) {
-                        (true, true) => INSTALLED_SELECTED_STYLE,
-                        (true, false) => INSTALLED_STYLE,
-                        (false, true) => UNINSTALLED_SELECTED_STYLE,
-                        (false, false) => UNINSTALLED_STYLE,
-                    },
-                );
-
-                let spans = if selected.contains(&real_index) {
-                    vec![index_span, padding_span, line_span, SELECTED]
-                } else {
-                    vec![index_span, padding_span, line_span]
-                };
-                Line::from(spans)
-            })
-            .collect(),
-        None => packages
-            .iter()
-            .enumerate()
-            .skip(skip)
-            .take(height - 5)
-            .map(|(i, line)| {
-                let index_span = Span::styled((i + 1).to_string(), INDEX_STYLE);
-                let padding_span = Span {
-                    content: PADDINGS[pad_to - (i + 1).ilog10() as usize].clone(),
-                    style: DEFAULT_STYLE,
-                };
-                let line_span = Span::styled(
-                    line,
-                    match (installed.contains(&i), current == i) {
-                        (true, true) => INSTALLED_SELECTED_STYLE,
-                        (true, false) => INSTALLED_STYLE,
-                        (false, true) => UNINSTALLED_SELECTED_STYLE,
-                        (false, false) => UNINSTALLED_STYLE,
-                    },
-                );
-
-                let spans = if selected.contains(&i) {
-                    vec![index_span, padding_span, line_span, SELECTED]
-                } else {
-                    vec![index_span, padding_span, line_span]
-                };
-                Line::from(spans)
-            })
-            .collect(),
+    // Start at a word if there is one nearby
+    if let Some(space) = description[start..hit.start].find(' ') {
+        start += space + 1;
+    }
+    let text = format!("…{}", &description[start..]);
+    let shift = '…'.len_utf8();
+    (text, hit.start - start + shift..hit.end - start + shift)
+}
+
+/// Formats a count with thousands separators, such as `12,345`.
+pub fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
     }
+    out
 }
 
 pub async fn get_info<'line>(
-    all_packages: &CompactStrings,
+    backend: &dyn Backend,
+    all_packages: &Catalog,
     index: usize,
-    installed_cache: &IntSet<usize>,
+    installed_cache: &Installed,
 ) -> Vec<Line<'line>> {
     if index >= all_packages.len() {
         return Vec::new();
     }
 
-    let mut cmd = Command::new("brew");
-
     if !installed_cache.contains(&index) {
         // Debounce so that we don't spam requests
         sleep(Duration::from_millis(200)).await;
     };
-    cmd.arg("info");
-
-    cmd.arg(&all_packages[index]);
 
-    let output = cmd_output(cmd).await;
+    let output = backend.info(&all_packages[index]).await;
     let lines = output.lines().map(ToOwned::to_owned).collect::<Vec<_>>();
 
     const KEY_STYLE: Style = Style {
@@ -267,46 +476,3 @@ pub async fn get_info<'line>(
 
     info
 }
-
-pub async fn check_installed(packages: &CompactStrings) -> IntSet<usize> {
-    const FORMULAE_PATH: &str = "/usr/local/Cellar";
-    const CASKS_PATH: &str = "/usr/local/Caskroom";
-
-    let mut out = IntSet::default();
-    let Ok(formulae) = std::fs::read_dir(FORMULAE_PATH) else {
-        return out;
-    };
-    let Ok(casks) = std::fs::read_dir(CASKS_PATH) else {
-        return out;
-    };
-
-    let mut set = HashSet::new();
-    for entry in formulae.chain(casks).filter_map(Result::ok) {
-        let Ok(ft) = entry.file_type() else {
-            continue;
-        };
-
-        if !ft.is_dir() {
-            continue;
-        }
-
-        set.insert(entry.file_name().into_string().unwrap());
-    }
-
-    for (pos, _) in packages
-        .iter()
-        .enumerate()
-        .filter(|(_, p)| set.contains(*p))
-    {
-        out.insert(pos);
-    }
-    out
-}
-
-async fn cmd_output(mut cmd: Command) -> String {
-    cmd.output()
-        .await
-        .ok()
-        .and_then(|output| String::from_utf8(output.stdout).ok())
-        .unwrap_or_default()
-}
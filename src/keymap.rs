@@ -0,0 +1,752 @@
+use std::fmt::{self, Display, Write};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::mode::Mode;
+
+/// Something a key can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Suspend,
+    Help,
+    EnterSelect,
+    EnterInsert,
+    Search,
+    DeleteWord,
+    DeleteBack,
+    DeleteForward,
+    KillToStart,
+    KillToEnd,
+    Yank,
+    CursorLeft,
+    CursorRight,
+    WordLeft,
+    WordRight,
+    CursorStart,
+    CursorEnd,
+    HistoryPrev,
+    HistoryNext,
+    ReverseSearch,
+    Up,
+    Down,
+    InfoUp,
+    InfoDown,
+    InfoHalfPageBack,
+    InfoHalfPageForward,
+    InfoPageBack,
+    InfoPageForward,
+    InfoTop,
+    InfoBottom,
+    InfoSearch,
+    InfoNextMatch,
+    InfoPrevMatch,
+    LeaveInfo,
+    PageBack,
+    PageForward,
+    Top,
+    Bottom,
+    ToggleSelect,
+    ClearSelection,
+    EnterVisual,
+    SelectRange,
+    DeselectRange,
+    SelectAll,
+    InvertSelection,
+    SelectInstalled,
+    SelectOutdated,
+    Basket,
+    SaveSet,
+    LoadSet,
+    SortNext,
+    SortReverse,
+    NextView,
+    PrevView,
+    FocusNext,
+    GrowResults,
+    ShrinkResults,
+    ToggleInfo,
+    FlipInfo,
+    Retry,
+    OpenHomepage,
+    EditSource,
+    ShowSource,
+    Install,
+    Remove,
+    Upgrade,
+}
+
+impl Action {
+    pub const ALL: &'static [Action] = &[
+        Action::Quit,
+        Action::Suspend,
+        Action::Help,
+        Action::EnterSelect,
+        Action::EnterInsert,
+        Action::Search,
+        Action::DeleteWord,
+        Action::DeleteBack,
+        Action::DeleteForward,
+        Action::KillToStart,
+        Action::KillToEnd,
+        Action::Yank,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::WordLeft,
+        Action::WordRight,
+        Action::CursorStart,
+        Action::CursorEnd,
+        Action::HistoryPrev,
+        Action::HistoryNext,
+        Action::ReverseSearch,
+        Action::Up,
+        Action::Down,
+        Action::InfoUp,
+        Action::InfoDown,
+        Action::InfoHalfPageBack,
+        Action::InfoHalfPageForward,
+        Action::InfoPageBack,
+        Action::InfoPageForward,
+        Action::InfoTop,
+        Action::InfoBottom,
+        Action::InfoSearch,
+        Action::InfoNextMatch,
+        Action::InfoPrevMatch,
+        Action::LeaveInfo,
+        Action::PageBack,
+        Action::PageForward,
+        Action::Top,
+        Action::Bottom,
+        Action::ToggleSelect,
+        Action::ClearSelection,
+        Action::EnterVisual,
+        Action::SelectRange,
+        Action::DeselectRange,
+        Action::SelectAll,
+        Action::InvertSelection,
+        Action::SelectInstalled,
+        Action::SelectOutdated,
+        Action::Basket,
+        Action::SaveSet,
+        Action::LoadSet,
+        Action::SortNext,
+        Action::SortReverse,
+        Action::NextView,
+        Action::PrevView,
+        Action::FocusNext,
+        Action::GrowResults,
+        Action::ShrinkResults,
+        Action::ToggleInfo,
+        Action::FlipInfo,
+        Action::Retry,
+        Action::OpenHomepage,
+        Action::EditSource,
+        Action::ShowSource,
+        Action::Install,
+        Action::Remove,
+        Action::Upgrade,
+    ];
+
+    /// The name used for this action in `bind` config keys.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Suspend => "suspend",
+            Action::Help => "help",
+            Action::EnterSelect => "select-mode",
+            Action::EnterInsert => "insert-mode",
+            Action::Search => "search",
+            Action::DeleteWord => "delete-word",
+            Action::DeleteBack => "delete-back",
+            Action::DeleteForward => "delete-forward",
+            Action::KillToStart => "kill-to-start",
+            Action::KillToEnd => "kill-to-end",
+            Action::Yank => "yank",
+            Action::CursorLeft => "cursor-left",
+            Action::CursorRight => "cursor-right",
+            Action::WordLeft => "word-left",
+            Action::WordRight => "word-right",
+            Action::CursorStart => "cursor-start",
+            Action::CursorEnd => "cursor-end",
+            Action::HistoryPrev => "history-prev",
+            Action::HistoryNext => "history-next",
+            Action::ReverseSearch => "reverse-search",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::InfoUp => "info-up",
+            Action::InfoDown => "info-down",
+            Action::InfoHalfPageBack => "info-half-page-back",
+            Action::InfoHalfPageForward => "info-half-page-forward",
+            Action::InfoPageBack => "info-page-back",
+            Action::InfoPageForward => "info-page-forward",
+            Action::InfoTop => "info-top",
+            Action::InfoBottom => "info-bottom",
+            Action::InfoSearch => "info-search",
+            Action::InfoNextMatch => "info-next-match",
+            Action::InfoPrevMatch => "info-prev-match",
+            Action::LeaveInfo => "leave-info",
+            Action::PageBack => "page-back",
+            Action::PageForward => "page-forward",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::ToggleSelect => "toggle-select",
+            Action::ClearSelection => "clear-selection",
+            Action::EnterVisual => "visual-mode",
+            Action::SelectRange => "select-range",
+            Action::DeselectRange => "deselect-range",
+            Action::SelectAll => "select-all",
+            Action::InvertSelection => "invert-selection",
+            Action::SelectInstalled => "select-installed",
+            Action::SelectOutdated => "select-outdated",
+            Action::Basket => "basket",
+            Action::SaveSet => "save-set",
+            Action::LoadSet => "load-set",
+            Action::SortNext => "sort-next",
+            Action::SortReverse => "sort-reverse",
+            Action::NextView => "next-view",
+            Action::PrevView => "prev-view",
+            Action::FocusNext => "focus-next",
+            Action::GrowResults => "grow-results",
+            Action::ShrinkResults => "shrink-results",
+            Action::ToggleInfo => "toggle-info",
+            Action::FlipInfo => "flip-info",
+            Action::Retry => "retry",
+            Action::OpenHomepage => "open-homepage",
+            Action::EditSource => "edit-source",
+            Action::ShowSource => "show-source",
+            Action::Install => "install",
+            Action::Remove => "remove",
+            Action::Upgrade => "upgrade",
+        }
+    }
+
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Exit breweri",
+            Action::Suspend => "Suspend breweri and return to the shell",
+            Action::Help => "Show or hide keybinds",
+            Action::EnterSelect => "Switch to select mode",
+            Action::EnterInsert => "Switch to insert mode",
+            Action::Search => "Search for query",
+            Action::DeleteWord => "Remove previous word",
+            Action::DeleteBack => "Remove previous character",
+            Action::DeleteForward => "Remove next character",
+            Action::KillToStart => "Cut everything before the cursor",
+            Action::KillToEnd => "Cut everything after the cursor",
+            Action::Yank => "Paste the last cut text",
+            Action::CursorLeft => "Move cursor left",
+            Action::CursorRight => "Move cursor right",
+            Action::WordLeft => "Move cursor one word left",
+            Action::WordRight => "Move cursor one word right",
+            Action::CursorStart => "Move cursor to start",
+            Action::CursorEnd => "Move cursor to end",
+            Action::HistoryPrev => "Recall previous query",
+            Action::HistoryNext => "Recall next query",
+            Action::ReverseSearch => "Search past queries",
+            Action::Up => "Move one row up",
+            Action::Down => "Move one row down",
+            Action::InfoUp => "Move info one row up",
+            Action::InfoDown => "Move info one row down",
+            Action::InfoHalfPageBack => "Scroll info half a page back",
+            Action::InfoHalfPageForward => "Scroll info half a page forwards",
+            Action::InfoPageBack => "Scroll info one page back",
+            Action::InfoPageForward => "Scroll info one page forwards",
+            Action::InfoTop => "Go to the start of the info",
+            Action::InfoBottom => "Go to the end of the info",
+            Action::InfoSearch => "Search the info",
+            Action::InfoNextMatch => "Go to the next line matching the info search",
+            Action::InfoPrevMatch => "Go to the previous line matching the info search",
+            Action::LeaveInfo => "Clear the info search, or else move focus back to the results",
+            Action::PageBack => "Move one page back",
+            Action::PageForward => "Move one page forwards",
+            Action::Top => "Go to start",
+            Action::Bottom => "Go to end",
+            Action::ToggleSelect => "Select/deselect package",
+            Action::ClearSelection => "Clear selections",
+            Action::EnterVisual => "Switch to visual mode to select a range",
+            Action::SelectRange => "Select every package in the range",
+            Action::DeselectRange => "Deselect every package in the range",
+            Action::SelectAll => "Select every shown package",
+            Action::InvertSelection => "Invert selection of shown packages",
+            Action::SelectInstalled => "Select every shown installed package",
+            Action::SelectOutdated => "Select every shown outdated package",
+            Action::Basket => "Show or hide every selected package",
+            Action::SaveSet => "Save the selection as a named set",
+            Action::LoadSet => "Select the packages in a saved set",
+            Action::SortNext => "Sort by the next mode",
+            Action::SortReverse => "Reverse the sort order",
+            Action::NextView => "Switch to the next view",
+            Action::PrevView => "Switch to the previous view",
+            Action::FocusNext => "Move focus to the other pane",
+            Action::GrowResults => "Move the splitter to give the results more room",
+            Action::ShrinkResults => "Move the splitter to give the info pane more room",
+            Action::ToggleInfo => "Hide or show the info pane",
+            Action::FlipInfo => "Move the info pane between the right and below",
+            Action::Retry => "Try loading the package list again after an error",
+            Action::OpenHomepage => "Open the package's homepage in $BROWSER",
+            Action::EditSource => "Open the package's Ruby file in $EDITOR",
+            Action::ShowSource => "Show where the package's Ruby file is in its tap",
+            Action::Install => "Install selected packages",
+            Action::Remove => "Remove selected packages",
+            Action::Upgrade => "Upgrade selected packages",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+}
+
+/// A key with its modifiers, normalised so that bindings can be compared directly.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    const fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    const fn ctrl(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::CONTROL)
+    }
+
+    const fn alt(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::ALT)
+    }
+
+    const fn char(c: char) -> Self {
+        Self::plain(KeyCode::Char(c))
+    }
+
+    /// Parses vim-style key notation such as `j`, `<C-j>`, `<Down>` or `<S-Tab>`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let Some(inner) = s.strip_prefix('<').and_then(|s| s.strip_suffix('>')) else {
+            let mut chars = s.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(Self::char(c)),
+                _ => None,
+            };
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        let mut name = inner;
+        while let Some((modifier, rest)) = name.split_once('-').filter(|(_, r)| !r.is_empty()) {
+            modifiers |= match modifier {
+                "C" | "c" => KeyModifiers::CONTROL,
+                "S" | "s" => KeyModifiers::SHIFT,
+                "M" | "m" | "A" | "a" => KeyModifiers::ALT,
+                _ => return None,
+            };
+            name = rest;
+        }
+
+        let code = match name.to_ascii_lowercase().as_str() {
+            "return" | "enter" | "cr" => KeyCode::Enter,
+            "escape" | "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backspace" | "bs" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pgup" | "pageup" => KeyCode::PageUp,
+            "pgdn" | "pagedown" => KeyCode::PageDown,
+            "lt" => KeyCode::Char('<'),
+            "gt" => KeyCode::Char('>'),
+            f if f.len() > 1 && f.starts_with('f') => KeyCode::F(f[1..].parse().ok()?),
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+
+        Some(Self::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers =
+            event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::SHIFT | KeyModifiers::ALT);
+        let code = match event.code {
+            // The case of the character already says whether shift was held
+            KeyCode::Char(c) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(if modifiers.is_empty() {
+                    c
+                } else {
+                    c.to_ascii_lowercase()
+                })
+            }
+            KeyCode::BackTab => {
+                modifiers.insert(KeyModifiers::SHIFT);
+                KeyCode::Tab
+            }
+            code => code,
+        };
+
+        Self { code, modifiers }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_owned(),
+            KeyCode::Char('<') => "lt".to_owned(),
+            KeyCode::Char(c) if self.modifiers.is_empty() => return f.write_char(c),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Return".to_owned(),
+            KeyCode::Esc => "Escape".to_owned(),
+            KeyCode::Tab => "Tab".to_owned(),
+            KeyCode::Backspace => "Backspace".to_owned(),
+            KeyCode::Delete => "Delete".to_owned(),
+            KeyCode::Insert => "Insert".to_owned(),
+            KeyCode::Left => "Left".to_owned(),
+            KeyCode::Right => "Right".to_owned(),
+            KeyCode::Up => "Up".to_owned(),
+            KeyCode::Down => "Down".to_owned(),
+            KeyCode::Home => "Home".to_owned(),
+            KeyCode::End => "End".to_owned(),
+            KeyCode::PageUp => "PgUp".to_owned(),
+            KeyCode::PageDown => "PgDn".to_owned(),
+            KeyCode::F(n) => format!("F{n}"),
+            _ => "?".to_owned(),
+        };
+
+        f.write_char('<')?;
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            f.write_str("C-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("M-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("S-")?;
+        }
+        f.write_str(&name)?;
+        f.write_char('>')
+    }
+}
+
+pub struct Binding {
+    pub mode: Mode,
+    pub key: Key,
+    pub action: Action,
+}
+
+/// Which keys trigger which actions in each mode.
+///
+/// Both key dispatch and every list of keybinds shown to the user go through this, so they can
+/// never disagree.
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+        use KeyCode::{
+            Backspace, Char, Delete, End, Enter, Esc, Home, Left, PageDown, PageUp, Right, F,
+        };
+
+        let mut keymap = Self {
+            bindings: Vec::new(),
+        };
+
+        for mode in Mode::ALL {
+            keymap.add(mode, Key::ctrl(Char('c')), Quit);
+            keymap.add(mode, Key::ctrl(Char('z')), Suspend);
+            keymap.add(mode, Key::plain(F(1)), Help);
+            keymap.add(mode, Key::plain(F(5)), Retry);
+        }
+
+        for (key, action) in [
+            (Key::plain(Esc), EnterSelect),
+            (Key::plain(Enter), Search),
+            (Key::ctrl(Char('w')), DeleteWord),
+            (Key::plain(Backspace), DeleteBack),
+            (Key::plain(Delete), DeleteForward),
+            (Key::ctrl(Char('u')), KillToStart),
+            (Key::ctrl(Char('k')), KillToEnd),
+            (Key::ctrl(Char('y')), Yank),
+            (Key::plain(Left), CursorLeft),
+            (Key::plain(Right), CursorRight),
+            (Key::ctrl(Left), WordLeft),
+            (Key::alt(Char('b')), WordLeft),
+            (Key::ctrl(Right), WordRight),
+            (Key::alt(Char('f')), WordRight),
+            (Key::plain(Home), CursorStart),
+            (Key::ctrl(Char('a')), CursorStart),
+            (Key::plain(End), CursorEnd),
+            (Key::ctrl(Char('e')), CursorEnd),
+            (Key::plain(KeyCode::Up), HistoryPrev),
+            (Key::ctrl(Char('p')), HistoryPrev),
+            (Key::plain(KeyCode::Down), HistoryNext),
+            (Key::ctrl(Char('n')), HistoryNext),
+            (Key::ctrl(Char('r')), ReverseSearch),
+        ] {
+            keymap.add(Mode::Insert, key, action);
+        }
+
+        for (key, action) in [
+            (Key::plain(Esc), EnterInsert),
+            (Key::char('i'), EnterInsert),
+            (Key::char('/'), EnterInsert),
+            (Key::char('q'), Quit),
+            (Key::char('?'), Help),
+            (Key::plain(Enter), Install),
+            (Key::char('R'), Remove),
+            (Key::char('U'), Upgrade),
+            (Key::ctrl(Char('j')), InfoDown),
+            (Key::ctrl(KeyCode::Down), InfoDown),
+            (Key::ctrl(Char('k')), InfoUp),
+            (Key::ctrl(KeyCode::Up), InfoUp),
+            (Key::char('h'), PageBack),
+            (Key::plain(Left), PageBack),
+            (Key::plain(PageUp), PageBack),
+            (Key::char('j'), Down),
+            (Key::plain(KeyCode::Down), Down),
+            (Key::char('k'), Up),
+            (Key::plain(KeyCode::Up), Up),
+            (Key::char('l'), PageForward),
+            (Key::plain(Right), PageForward),
+            (Key::plain(PageDown), PageForward),
+            (Key::char('g'), Top),
+            (Key::plain(Home), Top),
+            (Key::char('G'), Bottom),
+            (Key::plain(End), Bottom),
+            (Key::char(' '), ToggleSelect),
+            (Key::char('c'), ClearSelection),
+            (Key::char('v'), EnterVisual),
+            (Key::char('V'), EnterVisual),
+            (Key::char('A'), SelectAll),
+            (Key::char('~'), InvertSelection),
+            (Key::char('I'), SelectInstalled),
+            (Key::char('O'), SelectOutdated),
+            (Key::char('b'), Basket),
+            (Key::char('S'), SaveSet),
+            (Key::char('L'), LoadSet),
+            (Key::char('s'), SortNext),
+            (Key::char('r'), SortReverse),
+            (Key::char(']'), NextView),
+            (Key::char('['), PrevView),
+            (Key::plain(KeyCode::Tab), FocusNext),
+            (Key::char('>'), GrowResults),
+            (Key::char('<'), ShrinkResults),
+            (Key::char('z'), ToggleInfo),
+            (Key::char('|'), FlipInfo),
+            (Key::char('o'), OpenHomepage),
+            (Key::char('e'), EditSource),
+            (Key::char('p'), ShowSource),
+        ] {
+            keymap.add(Mode::Select, key, action);
+        }
+
+        for (key, action) in [
+            (Key::plain(Esc), EnterSelect),
+            (Key::char('v'), EnterSelect),
+            (Key::char('V'), EnterSelect),
+            (Key::char('?'), Help),
+            (Key::char(' '), SelectRange),
+            (Key::plain(Enter), SelectRange),
+            (Key::char('d'), DeselectRange),
+            (Key::char('x'), DeselectRange),
+            (Key::char('h'), PageBack),
+            (Key::plain(Left), PageBack),
+            (Key::plain(PageUp), PageBack),
+            (Key::char('j'), Down),
+            (Key::plain(KeyCode::Down), Down),
+            (Key::char('k'), Up),
+            (Key::plain(KeyCode::Up), Up),
+            (Key::char('l'), PageForward),
+            (Key::plain(Right), PageForward),
+            (Key::plain(PageDown), PageForward),
+            (Key::char('g'), Top),
+            (Key::plain(Home), Top),
+            (Key::char('G'), Bottom),
+            (Key::plain(End), Bottom),
+        ] {
+            keymap.add(Mode::Visual, key, action);
+        }
+
+        for (key, action) in [
+            (Key::char('j'), InfoDown),
+            (Key::plain(KeyCode::Down), InfoDown),
+            (Key::char('k'), InfoUp),
+            (Key::plain(KeyCode::Up), InfoUp),
+            (Key::ctrl(Char('d')), InfoHalfPageForward),
+            (Key::ctrl(Char('u')), InfoHalfPageBack),
+            (Key::char(' '), InfoPageForward),
+            (Key::ctrl(Char('f')), InfoPageForward),
+            (Key::plain(PageDown), InfoPageForward),
+            (Key::ctrl(Char('b')), InfoPageBack),
+            (Key::plain(PageUp), InfoPageBack),
+            (Key::char('g'), InfoTop),
+            (Key::plain(Home), InfoTop),
+            (Key::char('G'), InfoBottom),
+            (Key::plain(End), InfoBottom),
+            (Key::char('/'), InfoSearch),
+            (Key::char('n'), InfoNextMatch),
+            (Key::char('N'), InfoPrevMatch),
+            (Key::plain(Esc), LeaveInfo),
+        ] {
+            keymap.add(Mode::Info, key, action);
+        }
+
+        for (key, action) in [
+            (Key::char('q'), Basket),
+            (Key::plain(Esc), Basket),
+            (Key::char('b'), Basket),
+            (Key::char('?'), Help),
+            (Key::char('j'), Down),
+            (Key::plain(KeyCode::Down), Down),
+            (Key::char('k'), Up),
+            (Key::plain(KeyCode::Up), Up),
+            (Key::char('g'), Top),
+            (Key::plain(Home), Top),
+            (Key::char('G'), Bottom),
+            (Key::plain(End), Bottom),
+            (Key::char('d'), ToggleSelect),
+            (Key::char('x'), ToggleSelect),
+            (Key::char(' '), ToggleSelect),
+            (Key::plain(Delete), ToggleSelect),
+            (Key::char('c'), ClearSelection),
+            (Key::char('S'), SaveSet),
+            (Key::char('L'), LoadSet),
+            (Key::plain(Enter), Install),
+            (Key::char('R'), Remove),
+            (Key::char('U'), Upgrade),
+        ] {
+            keymap.add(Mode::Basket, key, action);
+        }
+
+        for (key, action) in [
+            (Key::char('q'), Help),
+            (Key::char('?'), Help),
+            (Key::plain(Esc), Help),
+            (Key::char('j'), Down),
+            (Key::plain(KeyCode::Down), Down),
+            (Key::char('k'), Up),
+            (Key::plain(KeyCode::Up), Up),
+            (Key::char('l'), PageForward),
+            (Key::plain(PageDown), PageForward),
+            (Key::char('h'), PageBack),
+            (Key::plain(PageUp), PageBack),
+            (Key::char('g'), Top),
+            (Key::plain(Home), Top),
+            (Key::char('G'), Bottom),
+            (Key::plain(End), Bottom),
+        ] {
+            keymap.add(Mode::Help, key, action);
+        }
+
+        keymap
+    }
+}
+
+impl Keymap {
+    fn add(&mut self, mode: Mode, key: Key, action: Action) {
+        self.bindings.push(Binding { mode, key, action });
+    }
+
+    /// The action `key` triggers in `mode`. Info mode falls back to select mode, so that actions
+    /// like installing still work from the info pane.
+    pub fn get(&self, mode: Mode, key: Key) -> Option<Action> {
+        let action = self
+            .bindings
+            .iter()
+            .find(|b| b.mode == mode && b.key == key)
+            .map(|b| b.action);
+        match action {
+            None if mode == Mode::Info => self.get(Mode::Select, key),
+            action => action,
+        }
+    }
+
+    /// The first key bound to `action` in `mode`, for pointing the user at it. Info mode falls
+    /// back to select mode as in [`Keymap::get`].
+    pub fn key(&self, mode: Mode, action: Action) -> Option<Key> {
+        let key = self
+            .bindings
+            .iter()
+            .find(|b| b.mode == mode && b.action == action)
+            .map(|b| b.key);
+        match key {
+            None if mode == Mode::Info => self.key(Mode::Select, action),
+            key => key,
+        }
+    }
+
+    /// Applies a `bind = MODE KEY ACTION` config value, replacing whatever `KEY` did before.
+    ///
+    /// `ACTION` may be `none` to unbind the key.
+    pub fn bind(&mut self, value: &str) -> Result<(), String> {
+        let mut words = value.split_whitespace();
+        let (Some(mode), Some(key), Some(action), None) =
+            (words.next(), words.next(), words.next(), words.next())
+        else {
+            return Err(format!("bind `{value}` should look like MODE KEY ACTION"));
+        };
+
+        let mode = Mode::from_name(mode).ok_or_else(|| format!("unknown mode `{mode}`"))?;
+        let key = Key::parse(key).ok_or_else(|| format!("unknown key `{key}`"))?;
+
+        self.bindings.retain(|b| !(b.mode == mode && b.key == key));
+        if action != "none" {
+            let action =
+                Action::from_name(action).ok_or_else(|| format!("unknown action `{action}`"))?;
+            self.add(mode, key, action);
+        }
+
+        Ok(())
+    }
+
+    /// Groups the keys for each action in `mode`, in the order the actions were first bound.
+    ///
+    /// Info mode also lists the select mode keys it falls back to.
+    pub fn describe(&self, mode: Mode) -> Vec<(String, &'static str)> {
+        let fallback = self.bindings.iter().filter(|b| {
+            mode == Mode::Info
+                && b.mode == Mode::Select
+                && !self
+                    .bindings
+                    .iter()
+                    .any(|o| o.mode == mode && o.key == b.key)
+        });
+
+        let mut out: Vec<(Action, String)> = Vec::new();
+        for binding in self
+            .bindings
+            .iter()
+            .filter(|b| b.mode == mode)
+            .chain(fallback)
+        {
+            match out.iter_mut().find(|(a, _)| *a == binding.action) {
+                Some((_, keys)) => {
+                    let _ = write!(keys, ", {}", binding.key);
+                }
+                None => out.push((binding.action, binding.key.to_string())),
+            }
+        }
+
+        out.into_iter()
+            .map(|(action, keys)| (keys, action.description()))
+            .collect()
+    }
+}
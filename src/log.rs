@@ -0,0 +1,27 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{dirs, error::Error};
+
+/// Appends `err` to `$XDG_STATE_HOME/breweri/log`, so that it can still be looked at once it is
+/// gone from the screen. Failing to do so is ignored, as there is nowhere else to report it.
+pub fn error(err: &Error) {
+    let Some(dir) = dirs::state_dir() else {
+        return;
+    };
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let _ = fs::create_dir_all(&dir).and_then(|()| {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("log"))?;
+        writeln!(file, "{secs}\terror: {err}")
+    });
+}
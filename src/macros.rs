@@ -37,12 +37,3 @@ macro_rules! style {
         $crate::style_inner!($($path: $value,)*)
     };
 }
-
-#[macro_export]
-macro_rules! cows {
-    ($($str:literal),*) => {
-        [
-            $(Cow::Borrowed($str)),*
-        ]
-    };
-}
@@ -1,21 +1,22 @@
-use bytemuck::NoUninit;
+use crate::error::Error;
 
-#[derive(Clone, Copy, NoUninit)]
-#[repr(u8)]
+/// Why there are no results, or what went wrong.
+#[derive(Clone)]
 pub enum Message {
     TrySearch,
-    ListingPackages,
-    Searching,
     NoResults,
+    Error(Error),
 }
 
 impl Message {
-    pub(crate) const fn as_str(&self) -> &'static str {
+    pub(crate) fn text(&self) -> String {
         match self {
-            Message::TrySearch => "Try searching for something",
-            Message::ListingPackages => "Listing packages...",
-            Message::Searching => "Searching for packages...",
-            Message::NoResults => "No results, try another query",
+            Message::TrySearch => "Try searching for something".to_owned(),
+            Message::NoResults => "No results, try another query".to_owned(),
+            Message::Error(err) if err.is_recoverable() => {
+                format!("{err} — {}, F5 to retry", err.hint())
+            }
+            Message::Error(err) => format!("{err} — {}", err.hint()),
         }
     }
 }
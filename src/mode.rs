@@ -1,8 +1,43 @@
 use bytemuck::NoUninit;
 
-#[derive(Clone, Copy, NoUninit)]
+#[derive(Clone, Copy, PartialEq, Eq, NoUninit)]
 #[repr(u8)]
 pub enum Mode {
     Insert,
     Select,
+    /// Select mode while marking a range of rows, started from `App::visual_anchor`.
+    Visual,
+    /// Select mode while the info pane has focus. Keys not bound here do what they do in select
+    /// mode.
+    Info,
+    /// While the basket of selected packages is open.
+    Basket,
+    /// While the keybinds are listed.
+    Help,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 6] = [
+        Mode::Insert,
+        Mode::Select,
+        Mode::Visual,
+        Mode::Info,
+        Mode::Basket,
+        Mode::Help,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Mode::Insert => "insert",
+            Mode::Select => "select",
+            Mode::Visual => "visual",
+            Mode::Info => "info",
+            Mode::Basket => "basket",
+            Mode::Help => "help",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Mode::ALL.into_iter().find(|m| m.name() == name)
+    }
 }
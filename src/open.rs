@@ -0,0 +1,46 @@
+use std::{env, path::Path, process::Command};
+
+/// Opens `url` with the first browser in `$BROWSER`, substituting it for `%s` if the entry has
+/// one, or with the system's opener if `$BROWSER` isn't set.
+pub fn browser(url: &str) -> Command {
+    let browsers = env::var("BROWSER").unwrap_or_default();
+    match browsers.split(':').find(|b| !b.trim().is_empty()) {
+        Some(browser) if browser.contains("%s") => shell_words(&browser.replace("%s", url)),
+        Some(browser) => {
+            let mut cmd = shell_words(browser);
+            cmd.arg(url);
+            cmd
+        }
+        None => {
+            let opener = if cfg!(target_os = "macos") {
+                "open"
+            } else {
+                "xdg-open"
+            };
+            let mut cmd = Command::new(opener);
+            cmd.arg(url);
+            cmd
+        }
+    }
+}
+
+/// Opens `path` with `$VISUAL`, `$EDITOR` or failing both `vi`.
+pub fn editor(path: &Path) -> Command {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_owned());
+    let mut cmd = shell_words(&editor);
+    cmd.arg(path);
+    cmd
+}
+
+/// The program named by the first word of `line`, with the rest as arguments, as in
+/// `EDITOR="code --wait"`.
+fn shell_words(line: &str) -> Command {
+    let mut words = line.split_whitespace();
+    let mut cmd = Command::new(words.next().unwrap_or_default());
+    cmd.args(words);
+    cmd
+}
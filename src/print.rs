@@ -0,0 +1,148 @@
+use std::{
+    io::{self, BufWriter, Write},
+    process::exit,
+};
+
+use crate::{
+    backend::Backend,
+    catalog::Catalog,
+    error::Error,
+    installed::Installed,
+    interface::search,
+    log,
+    sort::{Context, Sort, SortKey},
+    view::View,
+};
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    /// One tab-separated `name kind version installed` line per package.
+    Plain,
+    /// A JSON array of `{ name, kind, version, installed }` objects.
+    Json,
+}
+
+/// Searches for `query` and writes the matching packages to stdout instead of starting the TUI.
+///
+/// Returns whether anything matched. If the packages can't be listed, the error is reported on
+/// stderr and breweri exits with status 2.
+pub async fn run(
+    backend: &dyn Backend,
+    query: &str,
+    view: View,
+    sort: Sort,
+    format: Format,
+) -> io::Result<bool> {
+    let catalog = backend.list().await.unwrap_or_else(|err| fail(&err));
+    let installed = backend
+        .check_installed(&catalog)
+        .await
+        .unwrap_or_else(|err| fail(&err));
+    let installs = match sort.key {
+        SortKey::Installs(_) => Some(backend.installs(&catalog).await),
+        _ => None,
+    };
+    let sizes = match sort.key {
+        SortKey::Size => Some(backend.sizes(&catalog, &installed).await),
+        _ => None,
+    };
+    let leaves = match view {
+        View::Leaves => Some(backend.leaves(&catalog).await),
+        _ => None,
+    };
+    let ctx = Context {
+        catalog: &catalog,
+        installed: &installed,
+        installs: installs.as_ref(),
+        sizes: sizes.as_ref(),
+        query,
+        leaves: leaves.as_ref(),
+    };
+    let shown = search(query, &ctx, view, sort);
+
+    let indices: Box<dyn Iterator<Item = usize>> = match shown.get_vec() {
+        Some(v) => Box::new(v.iter().copied()),
+        None => Box::new(0..catalog.len()),
+    };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let found = match format {
+        Format::Plain => write_plain(&mut out, &catalog, &installed, indices)?,
+        Format::Json => write_json(&mut out, &catalog, &installed, indices)?,
+    };
+    out.flush()?;
+
+    Ok(found)
+}
+
+fn fail(err: &Error) -> ! {
+    log::error(err);
+    eprintln!("breweri: {err} — {}", err.hint());
+    exit(2)
+}
+
+fn write_plain(
+    out: &mut impl Write,
+    catalog: &Catalog,
+    installed: &Installed,
+    indices: impl Iterator<Item = usize>,
+) -> io::Result<bool> {
+    let mut found = false;
+    for i in indices {
+        found = true;
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            &catalog[i],
+            catalog.kind(i).as_str(),
+            catalog.version(i),
+            installed.contains(&i)
+        )?;
+    }
+
+    Ok(found)
+}
+
+fn write_json(
+    out: &mut impl Write,
+    catalog: &Catalog,
+    installed: &Installed,
+    indices: impl Iterator<Item = usize>,
+) -> io::Result<bool> {
+    let mut found = false;
+    out.write_all(b"[")?;
+    for i in indices {
+        if found {
+            out.write_all(b",")?;
+        }
+        found = true;
+
+        out.write_all(b"\n  {\"name\": ")?;
+        write_json_str(out, &catalog[i])?;
+        write!(
+            out,
+            ", \"kind\": \"{}\", \"version\": ",
+            catalog.kind(i).as_str()
+        )?;
+        write_json_str(out, catalog.version(i))?;
+        write!(out, ", \"installed\": {}}}", installed.contains(&i))?;
+    }
+    out.write_all(if found { b"\n]\n" } else { b"]\n" })?;
+
+    Ok(found)
+}
+
+fn write_json_str(out: &mut impl Write, s: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\t' => out.write_all(b"\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{c}")?,
+        }
+    }
+    out.write_all(b"\"")
+}
@@ -0,0 +1,67 @@
+use std::{fs, io, path::PathBuf};
+
+use crate::dirs;
+
+/// Saved selections live in `$XDG_DATA_HOME/breweri/sets`, one file per set holding a package
+/// name per line, so they still make sense after the catalog changes.
+fn dir() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("sets"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// Checks that `name` can be used as a file name.
+pub fn validate(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\0']) {
+        return Err(format!("`{name}` is not a valid set name"));
+    }
+
+    Ok(())
+}
+
+/// The names of every saved set, sorted.
+pub fn list() -> Vec<String> {
+    let mut names = dir()
+        .and_then(fs::read_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| validate(name).is_ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort_unstable();
+    names
+}
+
+pub fn save(name: &str, packages: &[&str]) -> Result<(), String> {
+    validate(name)?;
+    let write = || -> io::Result<()> {
+        let dir = dir()?;
+        fs::create_dir_all(&dir)?;
+
+        let mut contents = packages.join("\n");
+        contents.push('\n');
+        fs::write(dir.join(name), contents)
+    };
+
+    write().map_err(|err| format!("couldn't save set `{name}`: {err}"))
+}
+
+pub fn load(name: &str) -> Result<Vec<String>, String> {
+    validate(name)?;
+    let contents = dir()
+        .and_then(|dir| fs::read_to_string(dir.join(name)))
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => format!("no set named `{name}`"),
+            _ => format!("couldn't load set `{name}`: {err}"),
+        })?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
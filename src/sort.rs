@@ -0,0 +1,250 @@
+use std::cmp::Ordering;
+
+use crate::{
+    catalog::{Catalog, Installs, Period},
+    installed::{Installed, Leaves, Sizes},
+};
+
+/// What the results can be ordered by.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// The order Homebrew lists packages in, formulae first.
+    #[default]
+    Catalog,
+    /// How closely the name matches the query.
+    Relevance,
+    Name,
+    Kind,
+    /// The installed version, with installed packages first.
+    Installed,
+    Latest,
+    Description,
+    /// Homebrew's install analytics over a period.
+    Installs(Period),
+    /// When the installed version was installed.
+    Recent,
+    /// How much disk space the installed package takes up.
+    Size,
+    /// Outdated packages, then installed ones.
+    Outdated,
+}
+
+impl SortKey {
+    /// Every key, in the order `sort-next` cycles through them.
+    pub const ALL: &'static [SortKey] = &[
+        SortKey::Catalog,
+        SortKey::Relevance,
+        SortKey::Name,
+        SortKey::Installs(Period::Month),
+        SortKey::Installs(Period::Quarter),
+        SortKey::Installs(Period::Year),
+        SortKey::Recent,
+        SortKey::Size,
+        SortKey::Outdated,
+        SortKey::Kind,
+        SortKey::Installed,
+        SortKey::Latest,
+        SortKey::Description,
+    ];
+
+    /// The name used for this key in the `sort` config key and `--sort`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            SortKey::Catalog => "catalog",
+            SortKey::Relevance => "relevance",
+            SortKey::Name => "name",
+            SortKey::Kind => "kind",
+            SortKey::Installed => "installed",
+            SortKey::Latest => "latest",
+            SortKey::Description => "description",
+            SortKey::Installs(Period::Month) => "installs-30d",
+            SortKey::Installs(Period::Quarter) => "installs-90d",
+            SortKey::Installs(Period::Year) => "installs-365d",
+            SortKey::Recent => "recent",
+            SortKey::Size => "size",
+            SortKey::Outdated => "outdated",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|key| key.name() == name)
+            .ok_or_else(|| format!("unknown sort mode `{name}`"))
+    }
+
+    /// The installs period shown in the table, which follows the sort if it is by installs.
+    pub fn period(&self) -> Period {
+        match self {
+            SortKey::Installs(period) => *period,
+            _ => Period::Month,
+        }
+    }
+
+    /// Orders two packages by this key, most useful first.
+    fn compare(&self, ctx: &Context, a: usize, b: usize) -> Ordering {
+        let Context {
+            catalog,
+            installed,
+            installs,
+            sizes,
+            query,
+            ..
+        } = *ctx;
+
+        match self {
+            SortKey::Catalog => a.cmp(&b),
+            SortKey::Relevance => relevance(query, &catalog[a]).cmp(&relevance(query, &catalog[b])),
+            SortKey::Name => catalog[a].cmp(&catalog[b]),
+            SortKey::Kind => catalog.kind(a).cmp(&catalog.kind(b)),
+            // Installed packages come first either way
+            SortKey::Installed => match (installed.version(a), installed.version(b)) {
+                (Some(a), Some(b)) => natural_cmp(a, b).reverse(),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            },
+            SortKey::Latest => natural_cmp(catalog.version(a), catalog.version(b)).reverse(),
+            SortKey::Description => {
+                let (a, b) = (catalog.description(a), catalog.description(b));
+                // Leave packages without one at the end
+                a.is_empty().cmp(&b.is_empty()).then_with(|| a.cmp(b))
+            }
+            SortKey::Installs(period) => {
+                let count = |i| installs.map(|installs| installs.get(*period, i));
+                count(b).cmp(&count(a))
+            }
+            // Packages installed at an unknown time go after the rest, but before uninstalled ones
+            SortKey::Recent => {
+                let rank = |i| (installed.contains(&i), installed.installed_at(i));
+                rank(b).cmp(&rank(a))
+            }
+            SortKey::Size => {
+                let size = |i| sizes.and_then(|sizes| sizes.get(&i));
+                size(b).cmp(&size(a))
+            }
+            SortKey::Outdated => {
+                let rank = |i| (installed.is_outdated(catalog, i), installed.contains(&i));
+                rank(b).cmp(&rank(a))
+            }
+        }
+    }
+}
+
+/// Everything a [`SortKey`] or [`View`](crate::view::View) may need to look at.
+#[derive(Clone, Copy)]
+pub struct Context<'a> {
+    pub catalog: &'a Catalog,
+    pub installed: &'a Installed,
+    /// Only needed by [`SortKey::Installs`], which leaves packages in catalog order if they
+    /// aren't known.
+    pub installs: Option<&'a Installs>,
+    /// Only needed by [`SortKey::Size`], which puts every package last if they aren't known.
+    pub sizes: Option<&'a Sizes>,
+    /// The query the results were found with, for [`SortKey::Relevance`].
+    pub query: &'a str,
+    /// Only needed by [`View::Leaves`](crate::view::View::Leaves), which is empty if they aren't known.
+    pub leaves: Option<&'a Leaves>,
+}
+
+/// How the results are ordered.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sort {
+    pub key: SortKey,
+    pub reverse: bool,
+}
+
+impl Sort {
+    /// Whether sorting by this leaves packages in catalog order, so there is nothing to do.
+    pub fn is_catalog_order(&self, query: &str) -> bool {
+        !self.reverse
+            && match self.key {
+                SortKey::Catalog => true,
+                SortKey::Relevance => query.is_empty(),
+                _ => false,
+            }
+    }
+
+    /// Sorts package indices, falling back to catalog order for ties.
+    pub fn apply(&self, indices: &mut [usize], ctx: &Context) {
+        if self.key == SortKey::Catalog {
+            indices.sort_unstable();
+        } else {
+            indices.sort_by(|&a, &b| self.key.compare(ctx, a, b).then(a.cmp(&b)));
+        }
+
+        if self.reverse {
+            indices.reverse();
+        }
+    }
+
+    /// Describes the sort for the results title, such as `installs-30d ▼`.
+    pub fn describe(&self) -> String {
+        format!("{} {}", self.key.name(), self.arrow())
+    }
+
+    pub const fn arrow(&self) -> &'static str {
+        if self.reverse {
+            "▼"
+        } else {
+            "▲"
+        }
+    }
+}
+
+/// Ranks how well `name` matches `query`: exact matches first, then prefixes, then matches
+/// starting a word, then anything else, earlier and shorter matches breaking ties.
+fn relevance(query: &str, name: &str) -> (u8, usize, usize) {
+    if query.is_empty() {
+        return (0, 0, 0);
+    }
+
+    let Some(pos) = name.find(query) else {
+        return (4, 0, name.len());
+    };
+
+    let kind = if name == query {
+        0
+    } else if pos == 0 {
+        1
+    } else if name.match_indices(query).any(|(i, _)| {
+        name[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_alphanumeric())
+    }) {
+        2
+    } else {
+        3
+    };
+
+    (kind, pos, name.len())
+}
+
+/// Compares runs of digits by their value, so that `1.10` sorts after `1.9`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(ca), Some(cb)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+
+        if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            let (na, nb) = (digits(a), digits(b));
+            let (da, db) = (
+                a[..na].trim_start_matches('0'),
+                b[..nb].trim_start_matches('0'),
+            );
+            let ordering = da.len().cmp(&db.len()).then_with(|| da.cmp(db));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            (a, b) = (&a[na..], &b[nb..]);
+        } else {
+            if ca != cb {
+                return ca.cmp(&cb);
+            }
+            (a, b) = (&a[ca.len_utf8()..], &b[cb.len_utf8()..]);
+        }
+    }
+}
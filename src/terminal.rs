@@ -0,0 +1,121 @@
+use std::{
+    fs,
+    io::{self, Write},
+    panic,
+    path::PathBuf,
+    process::{exit, Command, ExitStatus},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::dirs;
+
+/// Set while another program has the terminal, so that the signals meant for it, such as Ctrl-C,
+/// don't bring breweri down as well.
+static HANDED_OVER: AtomicBool = AtomicBool::new(false);
+
+/// Switches to raw mode on the alternate screen with mouse capture, for drawing the TUI.
+pub fn enter() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+/// Puts the terminal back the way it was before [`enter`]. Errors are ignored, as this also
+/// runs while crashing, when there is nothing better to do than carry on.
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    );
+}
+
+/// Restores the terminal and stops breweri as Ctrl-Z would outside raw mode, then takes the
+/// terminal back once the shell resumes it with SIGCONT. The screen has to be redrawn in full
+/// afterwards.
+pub fn suspend() -> io::Result<()> {
+    restore();
+    // SAFETY: raise is async-signal-safe and SIGTSTP keeps its default action, stopping the
+    // whole process until SIGCONT
+    unsafe { libc::raise(libc::SIGTSTP) };
+    enter()
+}
+
+/// Restores the terminal for `command` to use and waits for it, then takes the terminal back.
+/// The screen has to be redrawn in full afterwards.
+///
+/// SIGINT, SIGTERM and SIGHUP are ignored until `command` exits, as a shell would while waiting
+/// on it.
+pub fn hand_over(command: &mut Command) -> io::Result<ExitStatus> {
+    HANDED_OVER.store(true, Ordering::SeqCst);
+    restore();
+    let status = command.status();
+    HANDED_OVER.store(false, Ordering::SeqCst);
+    enter()?;
+    status
+}
+
+/// Makes sure the terminal is restored however breweri goes down.
+///
+/// A panic anywhere, including in a background task, restores the terminal, prints the panic
+/// message and exits, as the interface can't be trusted afterwards. The message is also written
+/// to a crash report in `$XDG_STATE_HOME/breweri`. SIGINT, SIGTERM and SIGHUP restore the
+/// terminal and exit with the usual `128 + signal` status, unless another program has been
+/// handed the terminal.
+pub fn guard() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        default_hook(info);
+        if let Some(path) = write_crash_report(&info.to_string()) {
+            eprintln!("breweri: crash report written to {}", path.display());
+        }
+        exit(101);
+    }));
+
+    for (kind, number) in [
+        (SignalKind::interrupt(), 2),
+        (SignalKind::terminate(), 15),
+        (SignalKind::hangup(), 1),
+    ] {
+        let Ok(mut signal) = signal(kind) else {
+            continue;
+        };
+        tokio::spawn(async move {
+            while signal.recv().await.is_some() {
+                if !HANDED_OVER.load(Ordering::SeqCst) {
+                    restore();
+                    exit(128 + number);
+                }
+            }
+        });
+    }
+}
+
+/// Writes what panicked and where, along with a backtrace, to `crash-<seconds>` in the state
+/// directory. Returns where it went, if anywhere.
+fn write_crash_report(panic: &str) -> Option<PathBuf> {
+    let dir = dirs::state_dir()?;
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = dir.join(format!("crash-{secs}"));
+
+    fs::create_dir_all(&dir).ok()?;
+    let mut file = fs::File::create(&path).ok()?;
+    writeln!(file, "breweri {} crashed", env!("CARGO_PKG_VERSION")).ok()?;
+    writeln!(file, "{panic}").ok()?;
+    writeln!(file, "\n{}", std::backtrace::Backtrace::force_capture()).ok()?;
+    Some(path)
+}
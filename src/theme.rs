@@ -0,0 +1,165 @@
+use std::env;
+
+use tui::style::{Color, Modifier, Style};
+
+use crate::style;
+
+/// How everything breweri draws is styled.
+///
+/// Emphasis that carries meaning regardless of colour, such as the bold labels of the focused
+/// box, is added on top of these when drawing.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    /// Text and borders of the search box while typing, or of the results and info pane while
+    /// moving around them.
+    pub active: Style,
+    /// Text and borders of whichever of those isn't active.
+    pub inactive: Style,
+    /// The tab of the current view, on top of `active` or `inactive`.
+    pub tab: Style,
+    /// The border of the info pane while it is focused.
+    pub focused: Style,
+    pub header: Style,
+    /// Row numbers, descriptions and other secondary text.
+    pub dim: Style,
+    pub installed: Style,
+    pub uninstalled: Style,
+    /// The name of the package under the cursor, if it is installed.
+    pub installed_current: Style,
+    /// The name of the package under the cursor, if it isn't installed.
+    pub uninstalled_current: Style,
+    /// The latest version of packages with a newer version than the one installed.
+    pub outdated: Style,
+    /// The `!` after selected packages.
+    pub marker: Style,
+    /// What the query matched in the results, on top of the cell's own style.
+    pub query_match: Style,
+    /// What was searched for in the info pane.
+    pub info_match: Style,
+    pub install_hint: Style,
+    pub remove_hint: Style,
+    /// Errors in the status bar.
+    pub error: Style,
+    /// Keys in the keybind overlay and the basket.
+    pub key: Style,
+    /// The row under the cursor in the basket.
+    pub current: Style,
+}
+
+impl Theme {
+    /// For terminals with a dark background, and the default unless `NO_COLOR` is set.
+    pub const DARK: Theme = Theme {
+        active: style!(Color::White),
+        inactive: style!(Color::Gray),
+        tab: style! { mod: Modifier::BOLD.union(Modifier::REVERSED) },
+        focused: style! { fg: Color::White, mod: Modifier::BOLD },
+        header: style! { mod: Modifier::BOLD },
+        dim: style!(Color::Gray),
+        installed: style! { fg: Color::Green, mod: Modifier::BOLD },
+        uninstalled: style! { fg: Color::LightBlue, mod: Modifier::BOLD },
+        installed_current: style! { fg: Color::Yellow, bg: Color::Red, mod: Modifier::BOLD },
+        uninstalled_current: style! { fg: Color::Blue, bg: Color::Red, mod: Modifier::BOLD },
+        outdated: style! { fg: Color::Yellow, mod: Modifier::BOLD },
+        marker: style! { fg: Color::Yellow, mod: Modifier::BOLD },
+        query_match: style! { mod: Modifier::UNDERLINED.union(Modifier::BOLD) },
+        info_match: style! { fg: Color::Black, bg: Color::Yellow },
+        install_hint: style! { fg: Color::Green, mod: Modifier::BOLD },
+        remove_hint: style! { fg: Color::Red, mod: Modifier::BOLD },
+        error: style! { fg: Color::Red, mod: Modifier::BOLD },
+        key: style! { fg: Color::LightBlue, mod: Modifier::BOLD },
+        current: style! { bg: Color::Red },
+    };
+
+    /// For terminals with a light background, avoiding yellow and light colours.
+    pub const LIGHT: Theme = Theme {
+        active: style!(Color::Black),
+        inactive: style!(Color::DarkGray),
+        tab: style! { mod: Modifier::BOLD.union(Modifier::REVERSED) },
+        focused: style! { fg: Color::Black, mod: Modifier::BOLD },
+        header: style! { mod: Modifier::BOLD },
+        dim: style!(Color::DarkGray),
+        installed: style! { fg: Color::Green, mod: Modifier::BOLD },
+        uninstalled: style! { fg: Color::Blue, mod: Modifier::BOLD },
+        installed_current: style! { fg: Color::White, bg: Color::Green, mod: Modifier::BOLD },
+        uninstalled_current: style! { fg: Color::White, bg: Color::Blue, mod: Modifier::BOLD },
+        outdated: style! { fg: Color::Magenta, mod: Modifier::BOLD },
+        marker: style! { fg: Color::Magenta, mod: Modifier::BOLD },
+        query_match: style! { mod: Modifier::UNDERLINED.union(Modifier::BOLD) },
+        info_match: style! { fg: Color::Black, bg: Color::LightYellow },
+        install_hint: style! { fg: Color::Green, mod: Modifier::BOLD },
+        remove_hint: style! { fg: Color::Red, mod: Modifier::BOLD },
+        error: style! { fg: Color::Red, mod: Modifier::BOLD },
+        key: style! { fg: Color::Blue, mod: Modifier::BOLD },
+        current: style! { fg: Color::White, bg: Color::Blue },
+    };
+
+    /// Bright colours only, with the cursor shown in reverse video.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        active: style! { fg: Color::White, mod: Modifier::BOLD },
+        inactive: style!(Color::White),
+        tab: style! { mod: Modifier::BOLD.union(Modifier::REVERSED) },
+        focused: style! { fg: Color::LightYellow, mod: Modifier::BOLD },
+        header: style! { fg: Color::White, mod: Modifier::BOLD.union(Modifier::UNDERLINED) },
+        dim: style!(Color::White),
+        installed: style! { fg: Color::LightGreen, mod: Modifier::BOLD },
+        uninstalled: style! { fg: Color::LightCyan, mod: Modifier::BOLD },
+        installed_current: style! { fg: Color::Black, bg: Color::LightGreen, mod: Modifier::BOLD },
+        uninstalled_current: style! { fg: Color::Black, bg: Color::LightCyan, mod: Modifier::BOLD },
+        outdated: style! { fg: Color::LightYellow, mod: Modifier::BOLD },
+        marker: style! { fg: Color::LightYellow, mod: Modifier::BOLD },
+        query_match: style! { mod: Modifier::UNDERLINED.union(Modifier::BOLD) },
+        info_match: style! { fg: Color::Black, bg: Color::LightYellow },
+        install_hint: style! { fg: Color::LightGreen, mod: Modifier::BOLD },
+        remove_hint: style! { fg: Color::LightRed, mod: Modifier::BOLD },
+        error: style! { fg: Color::LightRed, mod: Modifier::BOLD },
+        key: style! { fg: Color::LightCyan, mod: Modifier::BOLD },
+        current: style! { fg: Color::Black, bg: Color::White },
+    };
+
+    /// No colours at all, for `NO_COLOR`, telling things apart with bold and reverse video.
+    pub const MONO: Theme = Theme {
+        active: style!(),
+        inactive: style!(),
+        tab: style! { mod: Modifier::BOLD.union(Modifier::REVERSED) },
+        focused: style! { mod: Modifier::BOLD },
+        header: style! { mod: Modifier::BOLD },
+        dim: style!(),
+        installed: style! { mod: Modifier::BOLD },
+        uninstalled: style!(),
+        installed_current: style! { mod: Modifier::BOLD.union(Modifier::REVERSED) },
+        uninstalled_current: style! { mod: Modifier::REVERSED },
+        outdated: style! { mod: Modifier::BOLD },
+        marker: style! { mod: Modifier::BOLD },
+        query_match: style! { mod: Modifier::UNDERLINED },
+        info_match: style! { mod: Modifier::REVERSED },
+        install_hint: style! { mod: Modifier::BOLD },
+        remove_hint: style! { mod: Modifier::BOLD },
+        error: style! { mod: Modifier::BOLD },
+        key: style! { mod: Modifier::BOLD },
+        current: style! { mod: Modifier::REVERSED },
+    };
+
+    /// Every built-in theme, by the name used for the `theme` config key and `--theme`.
+    const ALL: [(&'static str, &'static Theme); 4] = [
+        ("dark", &Theme::DARK),
+        ("light", &Theme::LIGHT),
+        ("high-contrast", &Theme::HIGH_CONTRAST),
+        ("mono", &Theme::MONO),
+    ];
+
+    pub fn from_name(name: &str) -> Result<&'static Theme, String> {
+        Self::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|&(_, theme)| theme)
+            .ok_or_else(|| format!("unknown theme `{name}`"))
+    }
+
+    /// [`Theme::MONO`] if `NO_COLOR` is set to anything, or [`Theme::DARK`] otherwise.
+    pub fn from_env() -> &'static Theme {
+        match env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => &Theme::MONO,
+            _ => &Theme::DARK,
+        }
+    }
+}
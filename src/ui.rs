@@ -0,0 +1,737 @@
+use tui::{
+    layout::{Alignment, Constraint, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::App,
+    interface::{format_results, group_digits},
+    keymap::Action,
+    message::Message,
+    mode::Mode,
+    sort::Sort,
+    view::View,
+};
+
+/// Where the info pane goes relative to the results.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum InfoPosition {
+    #[default]
+    Right,
+    Below,
+}
+
+/// How the screen is shared between the results and the info pane.
+#[derive(Clone, Copy)]
+pub struct Panes {
+    pub info: InfoPosition,
+    pub info_hidden: bool,
+    /// How much of the width, or the height with the info below, the results take up, as a
+    /// percentage.
+    pub split: u16,
+    /// Whether the terminal is narrower than [`Panes::COMPACT_WIDTH`], updated before each
+    /// frame. There is no room for the info pane beside the results then.
+    pub compact: bool,
+}
+
+impl Default for Panes {
+    fn default() -> Self {
+        Self {
+            info: InfoPosition::default(),
+            info_hidden: false,
+            split: 50,
+            compact: false,
+        }
+    }
+}
+
+impl Panes {
+    pub const MIN_SPLIT: u16 = 20;
+    pub const MAX_SPLIT: u16 = 80;
+    pub const COMPACT_WIDTH: u16 = 80;
+
+    /// Whether the info pane takes up any room, which it doesn't if it was hidden or if it
+    /// would be squeezed to the right of the results in a compact layout.
+    pub fn info_shown(&self) -> bool {
+        !(self.info_hidden || self.compact && self.info == InfoPosition::Right)
+    }
+
+    /// Parses the value of the `info` config key: `right`, `below` or `hidden`.
+    pub fn set_info(&mut self, value: &str) -> Result<(), String> {
+        match value {
+            "right" => self.info = InfoPosition::Right,
+            "below" => self.info = InfoPosition::Below,
+            "hidden" => self.info_hidden = true,
+            _ => {
+                return Err(format!(
+                    "info should be right, below or hidden, not `{value}`"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the value of the `split` config key, a percentage.
+    pub fn set_split(&mut self, value: &str) -> Result<(), String> {
+        match value.trim_end_matches('%').parse() {
+            Ok(split) if (Self::MIN_SPLIT..=Self::MAX_SPLIT).contains(&split) => {
+                self.split = split;
+                Ok(())
+            }
+            _ => Err(format!(
+                "split should be a percentage from {} to {}, not `{value}`",
+                Self::MIN_SPLIT,
+                Self::MAX_SPLIT
+            )),
+        }
+    }
+
+    /// Moves the splitter so that the results get `split` percent of the space, within limits.
+    pub fn resize(&mut self, split: u16) {
+        self.split = split.clamp(Self::MIN_SPLIT, Self::MAX_SPLIT);
+    }
+}
+
+/// Where each part of the screen is drawn, shared with mouse handling.
+pub struct Areas {
+    pub search: Rect,
+    pub results: Rect,
+    /// The table of packages inside `results`, header included.
+    pub table: Rect,
+    /// The package rows of `table`.
+    pub rows: Rect,
+    /// Only drawn while there are results, and empty if the info pane is hidden.
+    pub info: Rect,
+    /// Where the results and info pane meet, which can be dragged to resize them.
+    pub splitter: Rect,
+    pub status: Rect,
+}
+
+impl Areas {
+    /// The smallest terminal breweri draws anything but [`draw_too_small`] in, which leaves a
+    /// single row of results.
+    pub const MIN_WIDTH: u16 = 20;
+    pub const MIN_HEIGHT: u16 = 8;
+
+    pub fn fits(size: Rect) -> bool {
+        size.width >= Self::MIN_WIDTH && size.height >= Self::MIN_HEIGHT
+    }
+
+    pub fn new(size: Rect, panes: Panes) -> Self {
+        let [search, body, status] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(size);
+
+        let (results, info) = if !panes.info_shown() {
+            (body, Rect::default())
+        } else {
+            let constraints = [
+                Constraint::Percentage(panes.split),
+                Constraint::Percentage(100 - panes.split),
+            ];
+            let [results, info] = match panes.info {
+                InfoPosition::Right => Layout::horizontal(constraints),
+                InfoPosition::Below => Layout::vertical(constraints),
+            }
+            .areas(body);
+            (results, info)
+        };
+
+        let splitter = match panes.info {
+            _ if !panes.info_shown() => Rect::default(),
+            InfoPosition::Right => Rect {
+                x: info.x.saturating_sub(1),
+                width: 2,
+                ..info
+            },
+            InfoPosition::Below => Rect {
+                y: info.y.saturating_sub(1),
+                height: 2,
+                ..info
+            },
+        };
+
+        // Inside the border, with a column of padding on the left
+        let table = results.inner(&Margin::new(1, 1));
+        let table = Rect {
+            x: table.x + 1,
+            width: table.width.saturating_sub(1),
+            ..table
+        };
+        let [_, rows] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(table);
+
+        Self {
+            search,
+            results,
+            table,
+            rows,
+            info,
+            splitter,
+            status,
+        }
+    }
+}
+
+pub fn draw(app: &App, s: &mut Frame) {
+    let size = s.size();
+    if !Areas::fits(size) {
+        return draw_too_small(s, size);
+    }
+    let areas = Areas::new(size, app.panes);
+    let mode = app.mode();
+
+    let per_page = app.per_page;
+    let page = app.current / per_page;
+    let skipped = page * per_page;
+
+    // Show the visual range as selected before it is applied
+    let visual_selected;
+    let selected = match app.visual_range() {
+        Some(range) => {
+            visual_selected = app.selected.iter().copied().chain(range).collect();
+            &visual_selected
+        }
+        None => &app.selected,
+    };
+
+    let shown = app.shown.load();
+    let table = app.all_packages.get().and_then(|all_packages| {
+        app.installed.get().map(|installed| {
+            format_results(
+                all_packages,
+                &shown,
+                app.current,
+                selected,
+                per_page,
+                skipped,
+                installed,
+                app.installs.get(),
+                &app.columns,
+                app.sort,
+                app.searched(),
+                app.theme,
+                areas.table.width,
+                areas.table.height,
+            )
+        })
+    });
+
+    let theme = app.theme;
+    let search_style;
+    let shown_style;
+    let bold_search_style;
+    if mode == Mode::Insert || app.prompt.is_some() {
+        search_style = theme.active;
+        shown_style = theme.inactive;
+        bold_search_style = search_style.add_modifier(Modifier::BOLD);
+    } else {
+        search_style = theme.inactive;
+        shown_style = theme.active;
+        bold_search_style = search_style;
+    };
+
+    let (label, editor) = match (&app.prompt, &app.reverse_search) {
+        (Some(prompt), _) => (prompt.label(), &prompt.input),
+        (None, Some(search)) if search.found.is_none() && !search.pattern.is_empty() => (
+            format!(" Failing history `{}': ", search.pattern),
+            &app.query,
+        ),
+        (None, Some(search)) => (format!(" History `{}': ", search.pattern), &app.query),
+        (None, None) => (" Search: ".to_owned(), &app.query),
+    };
+    let label_width = label.width() as u16;
+    let (query, cursor) = editor.view(size.width.saturating_sub(label_width + 2) as usize);
+    let para = Paragraph::new(Line::from(vec![
+        Span::styled(label, bold_search_style),
+        Span::styled(query, search_style),
+    ]))
+    .block(
+        Block::default()
+            .title(Span::styled(" breweri ", bold_search_style))
+            .title_alignment(Alignment::Center)
+            .border_style(search_style)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded),
+    )
+    .alignment(Alignment::Left);
+    s.render_widget(para, areas.search);
+
+    let mut para = Block::default()
+        .borders(Borders::ALL)
+        .border_style(shown_style)
+        .border_type(BorderType::Rounded);
+    let mut tabs = vec![Span::raw(" ")];
+    for view in View::ALL {
+        let style = if view == app.view {
+            shown_style.patch(theme.tab)
+        } else {
+            shown_style
+        };
+        tabs.push(Span::styled(format!(" {} ", view.title()), style));
+        tabs.push(Span::raw(" "));
+    }
+    para = para.title(Line::from(tabs));
+    if mode == Mode::Visual {
+        para = para.title(
+            Line::from(Span::styled(
+                " visual ",
+                shown_style.add_modifier(Modifier::BOLD),
+            ))
+            .alignment(Alignment::Center),
+        );
+    }
+    if app.sort != Sort::default() {
+        para = para.title_bottom(
+            Line::from(format!(" sorted by {} ", app.sort.describe())).alignment(Alignment::Right),
+        );
+    }
+    s.render_widget(para, areas.results);
+
+    if let Some(results) = table.filter(|_| !shown.is_empty()) {
+        s.render_widget(results.table, areas.table);
+        *app.result_rows.borrow_mut() = results.rows;
+    }
+
+    if !shown.is_empty() && !areas.info.is_empty() {
+        let focused = mode == Mode::Info;
+        let (info, no_info) = {
+            let info_lock = app.info.lock();
+            (info_lock.clone(), info_lock.is_empty())
+        };
+
+        let mut border = Block::default()
+            .borders(Borders::ALL)
+            .border_style(shown_style)
+            .border_type(BorderType::Rounded);
+        if focused {
+            border = border.border_style(theme.focused);
+            let key = |action| key_label(app, Mode::Info, action);
+            let hint = match &app.info_search {
+                Some(pattern) => format!(
+                    " /{pattern}: {}, {}/{} to jump ",
+                    plural(app.info_matches(pattern).len(), "line"),
+                    key(Action::InfoNextMatch),
+                    key(Action::InfoPrevMatch),
+                ),
+                None => format!(
+                    " {}/{} scroll, {} find, {} back ",
+                    key(Action::InfoDown),
+                    key(Action::InfoUp),
+                    key(Action::InfoSearch),
+                    key(Action::FocusNext),
+                ),
+            };
+            border = border.title_bottom(Line::from(hint).alignment(Alignment::Center));
+        }
+        s.render_widget(Clear, areas.info);
+        s.render_widget(border, areas.info);
+
+        let [_, actions_area, _, text_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(2 + no_info as u16 * 2),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .areas(areas.info.inner(&Margin::new(2, 1)));
+        let mut hints = vec![
+            Line::from(Span::styled(
+                format!(
+                    "Press {} to (re)install selected packages",
+                    key_label(app, Mode::Select, Action::Install)
+                ),
+                theme.install_hint,
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "Press {} to uninstall selected packages",
+                    key_label(app, Mode::Select, Action::Remove)
+                ),
+                theme.remove_hint,
+            )),
+        ];
+        if no_info {
+            hints.push(Line::default());
+            hints.push(Line::from(Span::styled("Finding info...", theme.dim)));
+        }
+        let actions = Paragraph::new(hints).alignment(Alignment::Left);
+        s.render_widget(actions, actions_area);
+
+        // Remember where each line starts once wrapped, so searches can scroll to them
+        let mut layout = app.info_layout.borrow_mut();
+        layout.starts.clear();
+        let mut lines = 0;
+        for line in &info {
+            layout.starts.push(lines);
+            let wrapped = Paragraph::new(line.clone())
+                .wrap(Wrap { trim: false })
+                .line_count(text_area.width);
+            lines = lines.saturating_add(wrapped as u16);
+        }
+        layout.lines = lines;
+        layout.height = text_area.height;
+
+        let info = match &app.info_search {
+            Some(pattern) => info
+                .into_iter()
+                .map(|line| highlight_pattern(line, pattern, theme.info_match))
+                .collect(),
+            None => info,
+        };
+        let info = Paragraph::new(info)
+            .wrap(Wrap { trim: false })
+            .scroll((app.info_scroll, 0));
+        s.render_widget(info, text_area);
+    }
+
+    draw_status(app, s, areas.status, shown.is_empty());
+
+    if let Some(basket) = &app.basket {
+        draw_basket(app, s, basket.current);
+    }
+
+    if let Some(help) = &app.help {
+        draw_help(app, s, help.mode, help.scroll);
+    }
+
+    if (mode == Mode::Insert || app.prompt.is_some()) && app.help.is_none() {
+        s.set_cursor(
+            (cursor + label_width + 1).min(size.width.saturating_sub(2)),
+            1,
+        );
+    }
+}
+
+/// Draws the bar along the bottom: the mode, how many packages there are of each sort, what is
+/// going on in the background, and what the last action did.
+fn draw_status(app: &App, s: &mut Frame, area: Rect, no_results: bool) {
+    let theme = app.theme;
+    let separator = || Span::styled(" │ ", theme.dim);
+
+    let mut left = vec![Span::styled(
+        format!(" {} ", app.mode().name().to_uppercase()),
+        theme.active.patch(theme.tab),
+    )];
+    if let (Some(all_packages), Some(installed)) = (app.all_packages.get(), app.installed.get()) {
+        let outdated = installed
+            .iter()
+            .filter(|&i| installed.is_outdated(all_packages, i))
+            .count();
+        left.push(Span::raw(format!(
+            " {}/{} shown",
+            group_digits(app.shown_len() as u64),
+            group_digits(all_packages.len() as u64)
+        )));
+        left.push(separator());
+        left.push(Span::raw(format!("{} selected", app.selected.len())));
+        left.push(separator());
+        left.push(Span::raw(format!(
+            "{} installed, {outdated} outdated",
+            installed.iter().count()
+        )));
+    }
+
+    let activities = app.activities();
+    if !activities.is_empty() {
+        left.push(separator());
+        left.push(Span::styled(
+            format!("{} {}", app.spinner(), activities.join(", ")),
+            theme.dim,
+        ));
+    }
+
+    // Errors stay up until they are retried, even over the outcome of something else
+    let message = app.error_msg.lock().clone();
+    let (right, right_style) = match (&message, &app.outcome) {
+        (Message::Error(_), _) => (message.text(), theme.error),
+        (_, Some(outcome)) => (outcome.clone(), Style::default()),
+        _ if no_results && activities.is_empty() => (message.text(), Style::default()),
+        _ => (String::new(), Style::default()),
+    };
+
+    // The counts give way to the message if there isn't room for both
+    let right = format!(" {right} ");
+    let [left_area, right_area] =
+        Layout::horizontal([Constraint::Min(0), Constraint::Length(right.width() as u16)])
+            .areas(area);
+    s.render_widget(Paragraph::new(Line::from(left)), left_area);
+    s.render_widget(Paragraph::new(right).style(right_style), right_area);
+}
+
+/// Marks every occurrence of `pattern` in `line`, ignoring ASCII case. Occurrences split across
+/// spans aren't marked.
+fn highlight_pattern(line: Line<'static>, pattern: &str, match_style: Style) -> Line<'static> {
+    let pattern = pattern.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    for span in line.spans {
+        let lower = span.content.to_ascii_lowercase();
+        let mut rest = 0;
+        for (start, _) in lower.match_indices(&pattern) {
+            let end = start + pattern.len();
+            spans.push(Span::styled(
+                span.content[rest..start].to_owned(),
+                span.style,
+            ));
+            spans.push(Span::styled(
+                span.content[start..end].to_owned(),
+                span.style.patch(match_style),
+            ));
+            rest = end;
+        }
+        spans.push(Span::styled(span.content[rest..].to_owned(), span.style));
+    }
+    Line { spans, ..line }
+}
+
+/// Draws every selected package in a box over the results, with what each action would do to them.
+fn draw_basket(app: &App, s: &mut Frame, current: usize) {
+    let theme = app.theme;
+    let size = s.size();
+    let packages = app.basket_packages();
+    let (Some(all_packages), Some(installed)) = (app.all_packages.get(), app.installed.get())
+    else {
+        return;
+    };
+
+    let name_width = packages
+        .iter()
+        .map(|&i| all_packages[i].width())
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = packages
+        .iter()
+        .enumerate()
+        .map(|(row, &i)| {
+            let (style, state) = match installed.version(i) {
+                Some(_) if installed.is_outdated(all_packages, i) => (
+                    theme.installed,
+                    format!(
+                        "outdated {} -> {}",
+                        installed.version(i).unwrap_or_default(),
+                        all_packages.version(i)
+                    ),
+                ),
+                Some(version) => (theme.installed, format!("installed {version}")),
+                None => (theme.uninstalled, "not installed".to_owned()),
+            };
+
+            let line = Line::from(vec![
+                Span::styled(format!(" {:name_width$}", &all_packages[i]), style),
+                Span::raw(format!("  {:7}  {state}", all_packages.kind(i).as_str())),
+            ]);
+            if row == current {
+                line.patch_style(theme.current)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        lines.push(Line::from(
+            " Nothing selected, press Space on a package to select it.",
+        ));
+    }
+
+    let installed_count = packages.iter().filter(|i| installed.contains(i)).count();
+    let outdated_count = packages
+        .iter()
+        .filter(|&&i| installed.is_outdated(all_packages, i))
+        .count();
+    let skipped = packages.len() - installed_count;
+    let skipped = if skipped > 0 {
+        format!(", skipping {skipped} not installed")
+    } else {
+        String::new()
+    };
+    let summary = [
+        (
+            Action::Install,
+            format!("(re)install {}", plural(packages.len(), "package")),
+        ),
+        (
+            Action::Remove,
+            format!("uninstall {}{skipped}", plural(installed_count, "package")),
+        ),
+        (
+            Action::Upgrade,
+            format!(
+                "upgrade {} ({outdated_count} outdated){skipped}",
+                plural(installed_count, "package")
+            ),
+        ),
+    ]
+    .map(|(action, text)| (key_label(app, Mode::Basket, action), text));
+    let key_width = summary
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or_default();
+    let summary = summary
+        .into_iter()
+        .map(|(key, text)| {
+            Line::from(vec![
+                Span::styled(format!(" {key:key_width$}  "), theme.key),
+                Span::raw(text),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let width = size.width.saturating_sub(4).min(72);
+    let height = size
+        .height
+        .saturating_sub(2)
+        .min(lines.len() as u16 + summary.len() as u16 + 3);
+    let area = Rect {
+        x: (size.width - width) / 2,
+        y: (size.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" basket ({}) ", packages.len()),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+        .title_alignment(Alignment::Center)
+        .title_bottom(
+            Line::from(format!(
+                " {} remove, {} clear, {} close ",
+                key_label(app, Mode::Basket, Action::ToggleSelect),
+                key_label(app, Mode::Basket, Action::ClearSelection),
+                key_label(app, Mode::Basket, Action::Basket),
+            ))
+            .alignment(Alignment::Center),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let inner = block.inner(area);
+    s.render_widget(Clear, area);
+    s.render_widget(block, area);
+
+    let summary_height = (summary.len() as u16).min(inner.height);
+    // Leave a blank line between the packages and the summary
+    let list_area = Rect {
+        height: (inner.height - summary_height).saturating_sub(1),
+        ..inner
+    };
+    let summary_area = Rect {
+        y: inner.y + inner.height - summary_height,
+        height: summary_height,
+        ..inner
+    };
+
+    // Scroll just far enough to keep the current row in view
+    let rows = list_area.height.max(1) as usize;
+    let scroll = current.saturating_sub(rows - 1) as u16;
+    s.render_widget(Paragraph::new(lines).scroll((scroll, 0)), list_area);
+    s.render_widget(Paragraph::new(summary), summary_area);
+}
+
+/// Takes the place of everything else while the terminal is smaller than [`Areas::MIN_WIDTH`]
+/// by [`Areas::MIN_HEIGHT`]. Keys still work, so breweri can be quit or resized back.
+fn draw_too_small(s: &mut Frame, size: Rect) {
+    let text = vec![
+        Line::styled(
+            "Terminal too small",
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::from(format!(
+            "{}x{} < {}x{}",
+            size.width,
+            size.height,
+            Areas::MIN_WIDTH,
+            Areas::MIN_HEIGHT
+        )),
+    ];
+    let para = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    let height = (para.line_count(size.width) as u16).min(size.height);
+    let [_, area, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(height),
+        Constraint::Fill(1),
+    ])
+    .areas(size);
+    s.render_widget(para, area);
+}
+
+/// The first key bound to `action` in `mode`, for hints about what to press.
+fn key_label(app: &App, mode: Mode, action: Action) -> String {
+    app.keymap
+        .key(mode, action)
+        .map_or_else(|| "unbound".to_owned(), |key| key.to_string())
+}
+
+pub fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+/// Draws the keybinds for `mode` in a box over everything else.
+fn draw_help(app: &App, s: &mut Frame, mode: Mode, scroll: u16) {
+    let size = s.size();
+    let binds = app.keymap.describe(mode);
+    let key_width = binds
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let lines = binds
+        .into_iter()
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(format!(" {keys:key_width$}  "), app.theme.key),
+                Span::raw(description),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    let width = size.width.saturating_sub(4).min(72);
+    let height = size.height.saturating_sub(2).min(lines.len() as u16 + 2);
+    let area = Rect {
+        x: (size.width - width) / 2,
+        y: (size.height - height) / 2,
+        width,
+        height,
+    };
+
+    let title = format!(" {} mode keybinds ", mode.name());
+    let help = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    title,
+                    Style::default().add_modifier(Modifier::BOLD),
+                ))
+                .title_alignment(Alignment::Center)
+                .title_bottom(
+                    Line::from(format!(
+                        " {}/{} scroll, {} close ",
+                        key_label(app, Mode::Help, Action::Down),
+                        key_label(app, Mode::Help, Action::Up),
+                        key_label(app, Mode::Help, Action::Help),
+                    ))
+                    .alignment(Alignment::Center),
+                )
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .scroll((scroll, 0));
+    s.render_widget(Clear, area);
+    s.render_widget(help, area);
+}
@@ -0,0 +1,77 @@
+use crate::{catalog::Kind, sort::Context};
+
+/// Which part of the catalog is listed, before the query narrows it down.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum View {
+    #[default]
+    All,
+    Installed,
+    Outdated,
+    Casks,
+    Formulae,
+    /// Installed formulae that nothing else installed depends on.
+    Leaves,
+}
+
+impl View {
+    pub const ALL: [View; 6] = [
+        View::All,
+        View::Installed,
+        View::Outdated,
+        View::Casks,
+        View::Formulae,
+        View::Leaves,
+    ];
+
+    /// The name used for this view in the `view` config key and `--view`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            View::All => "all",
+            View::Installed => "installed",
+            View::Outdated => "outdated",
+            View::Casks => "casks",
+            View::Formulae => "formulae",
+            View::Leaves => "leaves",
+        }
+    }
+
+    pub const fn title(&self) -> &'static str {
+        match self {
+            View::All => "All",
+            View::Installed => "Installed",
+            View::Outdated => "Outdated",
+            View::Casks => "Casks",
+            View::Formulae => "Formulae",
+            View::Leaves => "Leaves",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|view| view.name() == name)
+            .ok_or_else(|| format!("unknown view `{name}`"))
+    }
+
+    /// The position of this view in [`View::ALL`].
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The view `offset` places after this one, wrapping around.
+    pub fn cycle(&self, offset: isize) -> Self {
+        let len = Self::ALL.len() as isize;
+        Self::ALL[(self.index() as isize + offset).rem_euclid(len) as usize]
+    }
+
+    pub fn matches(&self, ctx: &Context, index: usize) -> bool {
+        match self {
+            View::All => true,
+            View::Installed => ctx.installed.contains(&index),
+            View::Outdated => ctx.installed.is_outdated(ctx.catalog, index),
+            View::Casks => ctx.catalog.kind(index) == Kind::Cask,
+            View::Formulae => ctx.catalog.kind(index) == Kind::Formula,
+            View::Leaves => ctx.leaves.is_some_and(|leaves| leaves.contains(&index)),
+        }
+    }
+}